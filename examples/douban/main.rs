@@ -5,7 +5,9 @@ extern crate jets;
 
 use clap::{App, Arg};
 use jets::analysis::JiebaTokenizer;
+use jets::core::IndexWriterConfig;
 use jets::prelude::*;
+use jets::search::{FieldValueFactor, FunctionScore, Highlighter, Modifier, ScoreFunction};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fs::File;
@@ -27,6 +29,14 @@ fn init() {
     env_logger::builder().format_timestamp_millis().init();
 }
 
+/// Tokenizer of the dictionary stored with the index, the default one if none.
+fn open_tokenizer(output: &str) -> Result<JiebaTokenizer, Box<dyn Error>> {
+    match IndexReader::read_dictionary(output, JiebaTokenizer::KIND)? {
+        Some(dictionary) => JiebaTokenizer::from_dictionary(&dictionary),
+        None => Ok(JiebaTokenizer::default()),
+    }
+}
+
 // Download(TOKEN: auw6)
 // https://link.zhihu.com/?target=https%3A//pan.baidu.com/s/12IiX4p_fLg8CyidAjl8_Zw
 
@@ -35,6 +45,15 @@ fn init() {
 
 // SEARCH INDEX:
 // cargo run --example douban -- --output /tmp/jets/douban --search 恶魔
+
+// SEARCH INDEX, HIGHEST RATED FIRST:
+// cargo run --example douban -- --output /tmp/jets/douban --search 恶魔 --sort star
+
+// PROFILE SEARCH:
+// cargo run --example douban -- --output /tmp/jets/douban --search 恶魔 --profile
+
+// ANALYZE TITLE:
+// cargo run --example douban -- --output /tmp/jets/douban --analyze 恶魔的眼睛
 fn main() -> Result<(), Box<dyn Error>> {
     init();
 
@@ -46,7 +65,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             Arg::with_name("input")
                 .short("i")
                 .long("input")
-                .required(true)
+                .required_unless_one(&["search", "analyze"])
                 .takes_value(true)
                 .help("input film file."),
         )
@@ -58,6 +77,14 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .takes_value(true)
                 .help("index ouput dir."),
         )
+        .arg(
+            Arg::with_name("dict")
+                .short("d")
+                .long("dict")
+                .required(false)
+                .takes_value(true)
+                .help("user dictionary file."),
+        )
         .arg(
             Arg::with_name("search")
                 .short("s")
@@ -66,18 +93,74 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .takes_value(true)
                 .help("search film."),
         )
+        .arg(
+            Arg::with_name("sort")
+                .long("sort")
+                .required(false)
+                .takes_value(true)
+                .possible_values(&["star", "year"])
+                .help("sort found films by field, highest first."),
+        )
+        .arg(
+            Arg::with_name("popular")
+                .long("popular")
+                .required(false)
+                .takes_value(false)
+                .help("rank popular films higher by star."),
+        )
+        .arg(
+            Arg::with_name("profile")
+                .long("profile")
+                .required(false)
+                .takes_value(false)
+                .help("print the search profile."),
+        )
+        .arg(
+            Arg::with_name("analyze")
+                .short("a")
+                .long("analyze")
+                .required(false)
+                .takes_value(true)
+                .help("explain how a film title is analyzed."),
+        )
         .get_matches();
 
-    let input = cli.value_of("input").unwrap();
     let output = cli.value_of("output").unwrap();
     let search = cli.value_of("search");
+    let dict = cli.value_of("dict");
+
+    if let Some(text) = cli.value_of("analyze") {
+        // tokenized by the dictionary stored with the index, as titles are indexed.
+        let tokenizer = open_tokenizer(output)?;
+        for it in Analyzer::default().explain(&tokenizer, text) {
+            let token = it.get_token();
+            info!(
+                "{}\tposition={}, offsets=[{},{}), type={}, created_by={}, changed_by={:?}, removed_by={}",
+                token.get_text(),
+                token.get_position(),
+                token.get_start(),
+                token.get_end(),
+                token.get_kind(),
+                it.get_created_by(),
+                it.get_changed_by(),
+                it.get_removed_by().unwrap_or("-"),
+            );
+        }
+        return Ok(());
+    }
 
     match search {
         None => {
-            let f = File::open(input)?;
+            let f = File::open(cli.value_of("input").unwrap())?;
             let mut reader = BufReader::new(f);
             let mut line = String::new();
-            let mut writer = IndexWriter::open(output, JiebaTokenizer::default())?;
+            let tokenizer = match dict {
+                Some(path) => JiebaTokenizer::builder().dict(path).build()?,
+                None => JiebaTokenizer::default(),
+            };
+            // segments are flushed every 8MB of buffered films.
+            let config = IndexWriterConfig::default().ram_buffer_size(Some(8 * 1024 * 1024));
+            let writer = IndexWriter::open_with_config(output, tokenizer, config)?;
             while let Ok(read) = reader.read_line(&mut line) {
                 if read < 1 {
                     break;
@@ -87,17 +170,36 @@ fn main() -> Result<(), Box<dyn Error>> {
                     let doc = Document::builder()
                         .put("title", DocValue::Text(film.title), FLAG_TOKENIZED)
                         .put("year", DocValue::Text(film.year), 0)
+                        .put("star", DocValue::Double(film.star as f64), 0)
                         .build();
                     writer.push(doc)?;
                 }
                 line.clear();
             }
             writer.flush()?;
+            let stats = writer.stats();
+            info!(
+                "indexed {} films: segments={}, flush cost={}ms",
+                stats.get_flushed_docs(),
+                stats.get_flushes(),
+                stats.get_elapsed().as_millis()
+            );
         }
         Some(word) => {
             let reader = IndexReader::open(output)?;
             let searcher = IndexSearcher::from(reader);
-            let q = Query::from(Condition::Term("title".to_string(), word.to_string()));
+            let mut cond = Condition::term("title".to_string(), word.to_string());
+            if cli.is_present("popular") {
+                let star = FieldValueFactor::new("star").modifier(Modifier::Ln1p);
+                cond = FunctionScore::new(cond)
+                    .function(ScoreFunction::field_value_factor(star))
+                    .into();
+            }
+            let mut q = Query::from(cond);
+            if let Some(field) = cli.value_of("sort") {
+                q = q.sort(SortField::desc(field));
+            }
+            q = q.profile(cli.is_present("profile"));
             let mut now = Instant::now();
             let tops = searcher.search(&q);
             let cost1 = now.elapsed();
@@ -106,12 +208,24 @@ fn main() -> Result<(), Box<dyn Error>> {
             let cost2 = now.elapsed();
             let mut amount = 0usize;
             if let Some(docs) = result {
+                let highlighter = Highlighter::builder(open_tokenizer(output)?)
+                    .fragment_size(0)
+                    .build();
                 for doc in docs.iter() {
                     amount += 1;
-                    info!("found: {}", doc.get("title").unwrap());
+                    match highlighter
+                        .highlight_text(&q, "title", &doc.get("title").unwrap().to_string())
+                        .pop()
+                    {
+                        Some(title) => info!("found: {}", title),
+                        None => info!("found: {}", doc.get("title").unwrap()),
+                    }
                 }
             }
             info!("-------------------------------------");
+            if let Some(profile) = tops.profile() {
+                info!("profile:\n{}", profile);
+            }
             let cost = Duration::from_nanos((cost1.as_nanos() + cost2.as_nanos()) as u64);
             info!(
                 "amount={}, cost={}ms ({}ns: index={}, docs={})",
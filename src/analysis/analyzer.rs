@@ -0,0 +1,321 @@
+use super::{Script, StopWords, StopWordsCN, StopWordsSet, TokenFilter, Tokenizer};
+
+pub const TOKEN_TYPE_WORD: &str = "word";
+pub const TOKEN_TYPE_IDEOGRAPHIC: &str = "<IDEOGRAPHIC>";
+pub const TOKEN_TYPE_KANA: &str = "<KANA>";
+pub const TOKEN_TYPE_HANGUL: &str = "<HANGUL>";
+pub const TOKEN_TYPE_ALPHANUM: &str = "<ALPHANUM>";
+pub const TOKEN_TYPE_NUM: &str = "<NUM>";
+pub const TOKEN_TYPE_PUNCT: &str = "<PUNCT>";
+pub const TOKEN_TYPE_PINYIN: &str = "PINYIN";
+pub const TOKEN_TYPE_SYNONYM: &str = "SYNONYM";
+
+const STAGE_TOKENIZER: &str = "tokenizer";
+const STAGE_STOPWORDS: &str = "stopwords";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub(crate) text: String,
+    pub(crate) position: u32,
+    pub(crate) span: u32,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) kind: &'static str,
+}
+
+/// A token traced by `Analyzer::explain`: the stages created, changed and removed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzedToken {
+    token: Token,
+    created_by: String,
+    changed_by: Vec<String>,
+    removed_by: Option<String>,
+}
+
+/// Per-field analysis: stop words are dropped right after tokenizing, then token filters
+/// are applied in insertion order. Chinese stop words are used unless configured.
+pub struct Analyzer {
+    stopwords: Box<dyn StopWords>,
+    filters: Vec<Box<dyn TokenFilter>>,
+}
+
+pub struct AnalyzerBuilder {
+    inner: Analyzer,
+}
+
+impl Token {
+    pub fn new<A>(text: A, position: u32) -> Token
+    where
+        A: Into<String>,
+    {
+        Token {
+            text: text.into(),
+            position,
+            span: 1,
+            start: 0,
+            end: 0,
+            kind: TOKEN_TYPE_WORD,
+        }
+    }
+
+    pub fn kind(mut self, kind: &'static str) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Set the byte offsets of this token in the analyzed text.
+    pub fn offsets(mut self, start: usize, end: usize) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// Create a token at the same position and offsets, eg: pinyin of a Han token.
+    pub(crate) fn derive<A>(&self, text: A) -> Token
+    where
+        A: Into<String>,
+    {
+        Token::new(text, self.position).offsets(self.start, self.end)
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn get_position(&self) -> u32 {
+        self.position
+    }
+
+    /// Amount of positions covered by this token, greater than 1 for the last token of a
+    /// shorter alternative in a synonym graph.
+    pub fn get_span(&self) -> u32 {
+        self.span
+    }
+
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    pub fn get_end(&self) -> usize {
+        self.end
+    }
+
+    pub fn get_kind(&self) -> &'static str {
+        self.kind
+    }
+}
+
+impl AnalyzedToken {
+    pub fn get_token(&self) -> &Token {
+        &self.token
+    }
+
+    /// Name of the stage emitted this token: "tokenizer" or a token filter.
+    pub fn get_created_by(&self) -> &str {
+        &self.created_by
+    }
+
+    /// Names of the token filters changed position, type or span of this token.
+    pub fn get_changed_by(&self) -> &Vec<String> {
+        &self.changed_by
+    }
+
+    /// Name of the stage dropped or replaced this token: "stopwords" or a token filter.
+    pub fn get_removed_by(&self) -> Option<&str> {
+        self.removed_by.as_deref()
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.removed_by.is_some()
+    }
+}
+
+impl Default for Analyzer {
+    fn default() -> Analyzer {
+        Analyzer {
+            stopwords: Box::new(StopWordsCN),
+            filters: vec![],
+        }
+    }
+}
+
+impl Analyzer {
+    pub fn builder() -> AnalyzerBuilder {
+        AnalyzerBuilder {
+            inner: Analyzer::default(),
+        }
+    }
+
+    pub fn analyze(&self, tokenizer: &impl Tokenizer, input: &str) -> Vec<Token> {
+        let tokens = self.remove_stopwords(Self::tokenize(tokenizer, input));
+        self.filter(tokens)
+    }
+
+    /// Analyze input and trace every token through tokenizer, stop words and token filters,
+    /// including the ones removed. Useful for debugging tokenization.
+    pub fn explain(&self, tokenizer: &impl Tokenizer, input: &str) -> Vec<AnalyzedToken> {
+        let mut tokens = Self::tokenize(tokenizer, input);
+        let mut results: Vec<AnalyzedToken> = tokens
+            .iter()
+            .map(|it| AnalyzedToken {
+                token: it.clone(),
+                created_by: STAGE_TOKENIZER.to_string(),
+                changed_by: vec![],
+                removed_by: None,
+            })
+            .collect();
+        let next = self.remove_stopwords(tokens.clone());
+        trace(&mut results, &tokens, &next, STAGE_STOPWORDS);
+        tokens = next;
+        for it in self.filters.iter() {
+            let next = it.filter(tokens.clone());
+            trace(&mut results, &tokens, &next, it.name());
+            tokens = next;
+        }
+        results.sort_by_key(|it| it.token.position);
+        results
+    }
+
+    /// Analyze a query value: blank tokens are dropped with stop words and the others are
+    /// renumbered, so a filter sees the words of the value at consecutive positions.
+    pub(crate) fn analyze_query(
+        &self,
+        tokenizer: &(impl Tokenizer + ?Sized),
+        input: &str,
+    ) -> Vec<Token> {
+        let mut tokens = self.remove_stopwords(Self::tokenize(tokenizer, input));
+        tokens.retain(|it| !it.text.trim().is_empty());
+        for (i, it) in tokens.iter_mut().enumerate() {
+            it.position = i as u32;
+        }
+        self.filter(tokens)
+    }
+
+    pub fn filter(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        for it in self.filters.iter() {
+            tokens = it.filter(tokens);
+        }
+        tokens
+    }
+
+    fn tokenize(tokenizer: &(impl Tokenizer + ?Sized), input: &str) -> Vec<Token> {
+        let mut tokens = vec![];
+        let mut cursor = 0;
+        for (i, word) in tokenizer.tokenize(input).into_iter().enumerate() {
+            let start = offset_of(input, word, cursor);
+            cursor = start;
+            let token = Token::new(word, i as u32)
+                .offsets(start, start + word.len())
+                .kind(kind_of(word));
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    #[inline]
+    fn remove_stopwords(&self, tokens: Vec<Token>) -> Vec<Token> {
+        tokens
+            .into_iter()
+            .filter(|it| !self.stopwords.contains(&it.text))
+            .collect()
+    }
+}
+
+impl AnalyzerBuilder {
+    pub fn stopwords<S>(mut self, stopwords: S) -> Self
+    where
+        S: 'static + StopWords,
+    {
+        self.inner.stopwords = Box::new(stopwords);
+        self
+    }
+
+    /// Keep every token, eg: keyword-like text which is tokenized.
+    pub fn no_stopwords(self) -> Self {
+        self.stopwords(StopWordsSet::default())
+    }
+
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: 'static + TokenFilter,
+    {
+        self.inner.filters.push(Box::new(filter));
+        self
+    }
+
+    pub fn build(self) -> Analyzer {
+        self.inner
+    }
+}
+
+/// Byte offset of a word returned by tokenizer, which is usually a slice of input.
+#[inline]
+fn offset_of(input: &str, word: &str, cursor: usize) -> usize {
+    let begin = input.as_ptr() as usize;
+    let ptr = word.as_ptr() as usize;
+    if ptr >= begin && ptr + word.len() <= begin + input.len() {
+        return ptr - begin;
+    }
+    match input[cursor..].find(word) {
+        Some(n) => cursor + n,
+        None => cursor,
+    }
+}
+
+#[inline]
+fn kind_of(word: &str) -> &'static str {
+    let mut kind = TOKEN_TYPE_PUNCT;
+    for c in word.chars() {
+        match Script::detect(c) {
+            Some(Script::Han) => return TOKEN_TYPE_IDEOGRAPHIC,
+            Some(Script::Kana) => return TOKEN_TYPE_KANA,
+            Some(Script::Hangul) => return TOKEN_TYPE_HANGUL,
+            Some(_) => kind = TOKEN_TYPE_ALPHANUM,
+            None if c.is_numeric() && kind == TOKEN_TYPE_PUNCT => kind = TOKEN_TYPE_NUM,
+            None => (),
+        }
+    }
+    kind
+}
+
+/// Compare tokens before and after a stage: tokens with same text and offsets are changed,
+/// the missing ones are removed and the others are created by this stage.
+fn trace(results: &mut Vec<AnalyzedToken>, before: &[Token], after: &[Token], stage: &str) {
+    let mut rest: Vec<&Token> = after.iter().collect();
+    let mut missing = vec![];
+    for it in before {
+        match rest.iter().position(|x| *x == it) {
+            Some(n) => {
+                rest.remove(n);
+            }
+            None => missing.push(it),
+        }
+    }
+    for it in missing {
+        let found = match results
+            .iter_mut()
+            .find(|x| !x.is_removed() && x.token == *it)
+        {
+            Some(found) => found,
+            None => continue,
+        };
+        let same = rest
+            .iter()
+            .position(|x| x.text == it.text && x.start == it.start && x.end == it.end);
+        match same {
+            Some(n) => {
+                found.token = rest.remove(n).clone();
+                found.changed_by.push(stage.to_string());
+            }
+            None => found.removed_by = Some(stage.to_string()),
+        }
+    }
+    for it in rest {
+        results.push(AnalyzedToken {
+            token: it.clone(),
+            created_by: stage.to_string(),
+            changed_by: vec![],
+            removed_by: None,
+        });
+    }
+}
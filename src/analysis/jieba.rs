@@ -1,13 +1,224 @@
 use super::Tokenizer;
+use crate::spi::Result;
 use jieba_rs::Jieba;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CutMode {
+    /// Most accurate segmentation, no overlapping words.
+    Precise,
+    /// Every word found in the dictionary, overlapping.
+    Full,
+    /// Precise words then long words cut again, the default for indexing.
+    Search,
+}
 
-#[derive(Default)]
 pub struct JiebaTokenizer {
     inner: Jieba,
+    mode: CutMode,
+    hmm: bool,
+    words: Vec<UserWord>,
+}
+
+pub struct JiebaTokenizerBuilder {
+    mode: CutMode,
+    hmm: bool,
+    dicts: Vec<PathBuf>,
+    words: Vec<UserWord>,
+}
+
+#[derive(Debug, Clone)]
+struct UserWord {
+    word: String,
+    freq: Option<usize>,
+    tag: Option<String>,
+}
+
+impl Default for JiebaTokenizer {
+    fn default() -> JiebaTokenizer {
+        JiebaTokenizer {
+            inner: Jieba::default(),
+            mode: CutMode::Search,
+            hmm: false,
+            words: vec![],
+        }
+    }
 }
 
 impl Tokenizer for JiebaTokenizer {
     fn tokenize<'a>(&self, input: &'a str) -> Vec<&'a str> {
-        self.inner.cut_for_search(input, false)
+        match self.mode {
+            CutMode::Precise => self.inner.cut(input, self.hmm),
+            CutMode::Full => self.inner.cut_all(input),
+            CutMode::Search => self.inner.cut_for_search(input, self.hmm),
+        }
+    }
+
+    /// Cut mode and user words, None for the default tokenizer.
+    fn dictionary(&self) -> Option<String> {
+        if self.words.is_empty() && self.mode == CutMode::Search && !self.hmm {
+            return None;
+        }
+        Some(self.to_dictionary())
+    }
+
+    fn load_dictionary(&mut self, dictionary: &str) -> Result<()> {
+        *self = Self::from_dictionary(dictionary)?;
+        Ok(())
+    }
+
+    fn kind(&self) -> &str {
+        Self::KIND
+    }
+}
+
+impl JiebaTokenizer {
+    pub const KIND: &'static str = "jieba";
+
+    pub fn builder() -> JiebaTokenizerBuilder {
+        JiebaTokenizerBuilder {
+            mode: CutMode::Search,
+            hmm: false,
+            dicts: vec![],
+            words: vec![],
+        }
+    }
+
+    /// Tokenizer of a dictionary: a `#mode=.. hmm=..` line then user words, see
+    /// `Tokenizer::dictionary` and `IndexReader::read_dictionary`.
+    pub fn from_dictionary(dictionary: &str) -> Result<JiebaTokenizer> {
+        let mut bu = Self::builder();
+        for line in dictionary.lines() {
+            if let Some(header) = line.strip_prefix('#') {
+                for kv in header.split_whitespace() {
+                    let mut it = kv.splitn(2, '=');
+                    match (it.next(), it.next()) {
+                        (Some("mode"), Some(v)) => bu = bu.mode(CutMode::parse(v)?),
+                        (Some("hmm"), Some(v)) => bu = bu.hmm(v == "true"),
+                        _ => (),
+                    }
+                }
+            } else if let Some(word) = UserWord::parse(line) {
+                bu.words.push(word);
+            }
+        }
+        bu.build()
+    }
+
+    fn to_dictionary(&self) -> String {
+        let mut dictionary = format!("#mode={} hmm={}\n", self.mode.name(), self.hmm);
+        for it in self.words.iter() {
+            dictionary.push_str(&it.word);
+            if let Some(freq) = it.freq {
+                dictionary.push_str(&format!(" {}", freq));
+            }
+            if let Some(tag) = &it.tag {
+                dictionary.push_str(&format!(" {}", tag));
+            }
+            dictionary.push('\n');
+        }
+        dictionary
+    }
+
+    pub fn get_mode(&self) -> CutMode {
+        self.mode
+    }
+}
+
+impl JiebaTokenizerBuilder {
+    pub fn mode(mut self, mode: CutMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn hmm(mut self, enabled: bool) -> Self {
+        self.hmm = enabled;
+        self
+    }
+
+    /// Load a user dictionary: one word per line as `word [freq] [tag]`, `#` for comments.
+    pub fn dict<P>(mut self, path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.dicts.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Add a user word, the frequency is suggested by jieba if absent.
+    pub fn word<A>(mut self, word: A, freq: Option<usize>, tag: Option<&str>) -> Self
+    where
+        A: Into<String>,
+    {
+        self.words.push(UserWord {
+            word: word.into(),
+            freq,
+            tag: tag.map(|it| it.to_string()),
+        });
+        self
+    }
+
+    pub fn build(self) -> Result<JiebaTokenizer> {
+        let mut words = vec![];
+        for path in self.dicts.iter() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                if let Some(word) = UserWord::parse(&line?) {
+                    words.push(word);
+                }
+            }
+        }
+        words.extend(self.words);
+        let mut inner = Jieba::default();
+        for it in words.iter() {
+            inner.add_word(&it.word, it.freq, it.tag.as_deref());
+        }
+        Ok(JiebaTokenizer {
+            inner,
+            mode: self.mode,
+            hmm: self.hmm,
+            words,
+        })
+    }
+}
+
+impl CutMode {
+    fn name(&self) -> &'static str {
+        match self {
+            CutMode::Precise => "precise",
+            CutMode::Full => "full",
+            CutMode::Search => "search",
+        }
+    }
+
+    fn parse(name: &str) -> Result<CutMode> {
+        match name {
+            "precise" => Ok(CutMode::Precise),
+            "full" => Ok(CutMode::Full),
+            "search" => Ok(CutMode::Search),
+            _ => Err(format!("invalid cut mode: {}", name).into()),
+        }
+    }
+}
+
+impl UserWord {
+    fn parse(line: &str) -> Option<UserWord> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let mut parts = line.split_whitespace();
+        let word = parts.next()?.to_string();
+        let mut freq = None;
+        let mut tag = None;
+        for it in parts {
+            match it.parse::<usize>() {
+                Ok(n) if freq.is_none() && tag.is_none() => freq = Some(n),
+                _ => tag = Some(it.to_string()),
+            }
+        }
+        Some(UserWord { word, freq, tag })
     }
 }
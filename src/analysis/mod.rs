@@ -1,10 +1,60 @@
+mod analyzer;
 mod jieba;
+mod pinyin;
+mod script;
+mod standard;
 mod stopwords;
+mod synonym;
+
+use crate::spi::Result;
 
 // https://nitschinger.at/Text-Analysis-in-Rust-Tokenization/
-pub trait Tokenizer {
+pub trait Tokenizer: Send + Sync {
     fn tokenize<'a>(&self, input: &'a str) -> Vec<&'a str>;
+
+    /// Custom dictionary stored with an index by its writer, so readers tokenize the same way.
+    fn dictionary(&self) -> Option<String> {
+        None
+    }
+
+    /// Load the dictionary stored with an index, called by a writer opened without one.
+    fn load_dictionary(&mut self, _dictionary: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Kind stored with the dictionary, a dictionary is only loaded by tokenizers of its kind.
+    fn kind(&self) -> &str {
+        let full = std::any::type_name::<Self>();
+        match full.rfind("::") {
+            Some(n) => &full[n + 2..],
+            None => full,
+        }
+    }
+}
+
+pub trait TokenFilter: Send + Sync {
+    fn filter(&self, tokens: Vec<Token>) -> Vec<Token>;
+
+    /// Name of this filter in analysis explanations.
+    fn name(&self) -> &str {
+        let full = std::any::type_name::<Self>();
+        match full.rfind("::") {
+            Some(n) => &full[n + 2..],
+            None => full,
+        }
+    }
 }
 
-pub use jieba::JiebaTokenizer;
-pub use stopwords::{StopWords, StopWordsCN};
+pub use analyzer::{
+    AnalyzedToken, Analyzer, AnalyzerBuilder, Token, TOKEN_TYPE_ALPHANUM, TOKEN_TYPE_HANGUL,
+    TOKEN_TYPE_IDEOGRAPHIC, TOKEN_TYPE_KANA, TOKEN_TYPE_NUM, TOKEN_TYPE_PINYIN, TOKEN_TYPE_PUNCT,
+    TOKEN_TYPE_SYNONYM, TOKEN_TYPE_WORD,
+};
+pub use jieba::{CutMode, JiebaTokenizer, JiebaTokenizerBuilder};
+pub use pinyin::PinyinFilter;
+pub use script::{Script, ScriptTokenizer, ScriptTokenizerBuilder};
+pub use standard::{StandardTokenizer, WhitespaceTokenizer};
+pub use stopwords::{
+    StopWords, StopWordsCN, StopWordsEN, StopWordsOverride, StopWordsSet, StopWordsUnion,
+};
+pub use synonym::SynonymFilter;
@@ -0,0 +1,194 @@
+use super::{Token, TokenFilter, TOKEN_TYPE_PINYIN};
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref PINYIN_TABLE: HashMap<char, Vec<&'static str>> = {
+        let mut m = HashMap::new();
+        for line in include_str!("data.txt").lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let mut it = line.splitn(2, ' ');
+            if let (Some(han), Some(py)) = (it.next(), it.next()) {
+                if let Some(c) = han.chars().next() {
+                    m.insert(c, py.split(',').collect());
+                }
+            }
+        }
+        m
+    };
+}
+
+const DEFAULT_MAX_COMBINATIONS: usize = 16;
+
+/// Emits pinyin variants for tokens containing Han characters, at the same position
+/// as the original token. Eg: "恶魔" => "èmó" (full), "emo" (toneless), "em" (initials),
+/// "e", "mo" (syllables). Variants are emitted for every reading of polyphones, eg: "wumo".
+///
+/// Query values with syllables, like "e mo", match when tokenized without this filter,
+/// see `IndexSearcher::set_tokenizer`.
+pub struct PinyinFilter {
+    keep_original: bool,
+    full: bool,
+    toneless: bool,
+    initials: bool,
+    syllables: bool,
+    max_combinations: usize,
+}
+
+impl Default for PinyinFilter {
+    fn default() -> PinyinFilter {
+        PinyinFilter {
+            keep_original: true,
+            full: true,
+            toneless: true,
+            initials: true,
+            syllables: true,
+            max_combinations: DEFAULT_MAX_COMBINATIONS,
+        }
+    }
+}
+
+impl PinyinFilter {
+    pub fn keep_original(mut self, enabled: bool) -> Self {
+        self.keep_original = enabled;
+        self
+    }
+
+    pub fn full(mut self, enabled: bool) -> Self {
+        self.full = enabled;
+        self
+    }
+
+    pub fn toneless(mut self, enabled: bool) -> Self {
+        self.toneless = enabled;
+        self
+    }
+
+    pub fn initials(mut self, enabled: bool) -> Self {
+        self.initials = enabled;
+        self
+    }
+
+    /// Emit each toneless syllable as a token.
+    pub fn syllables(mut self, enabled: bool) -> Self {
+        self.syllables = enabled;
+        self
+    }
+
+    /// Max combinations of polyphone readings emitted per token, 16 by default.
+    pub fn max_combinations(mut self, n: usize) -> Self {
+        self.max_combinations = n.max(1);
+        self
+    }
+
+    /// Returns the full (tone marked) pinyin readings of each character of input, primary
+    /// reading first, None if it has no Han character.
+    pub fn readings(input: &str) -> Option<Vec<Vec<String>>> {
+        let mut found = false;
+        let mut results = vec![];
+        for c in input.chars() {
+            match PINYIN_TABLE.get(&c) {
+                Some(py) => {
+                    found = true;
+                    results.push(py.iter().map(|it| it.to_string()).collect());
+                }
+                None => results.push(vec![c.to_lowercase().to_string()]),
+            }
+        }
+        if found {
+            Some(results)
+        } else {
+            None
+        }
+    }
+
+    fn variants(&self, token: &Token) -> Vec<String> {
+        let readings = match Self::readings(&token.text) {
+            Some(v) => v,
+            None => return vec![],
+        };
+        // primary readings first, the last characters vary fastest.
+        let mut combinations: Vec<Vec<&str>> = vec![vec![]];
+        for it in readings.iter() {
+            combinations = combinations
+                .iter()
+                .flat_map(|prefix| {
+                    it.iter().map(move |s| {
+                        let mut next = prefix.clone();
+                        next.push(s.as_str());
+                        next
+                    })
+                })
+                .take(self.max_combinations)
+                .collect();
+        }
+        let mut results: Vec<String> = vec![];
+        let mut push = |s: String| {
+            if s != token.text && !results.contains(&s) {
+                results.push(s);
+            }
+        };
+        if self.full {
+            for it in combinations.iter() {
+                push(it.concat());
+            }
+        }
+        if self.toneless {
+            for it in combinations.iter() {
+                push(it.iter().map(|s| strip_tones(s)).collect());
+            }
+        }
+        if self.initials {
+            for it in combinations.iter() {
+                push(
+                    it.iter()
+                        .filter_map(|s| strip_tones(s).chars().next())
+                        .collect(),
+                );
+            }
+        }
+        if self.syllables {
+            for s in readings.iter().flatten() {
+                push(strip_tones(s));
+            }
+        }
+        results
+    }
+}
+
+impl TokenFilter for PinyinFilter {
+    fn filter(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut results = vec![];
+        for token in tokens {
+            let variants: Vec<Token> = self
+                .variants(&token)
+                .into_iter()
+                .map(|it| token.derive(it).kind(TOKEN_TYPE_PINYIN))
+                .collect();
+            if self.keep_original || variants.is_empty() {
+                results.push(token);
+            }
+            results.extend(variants);
+        }
+        results
+    }
+}
+
+#[inline]
+fn strip_tones(syllable: &str) -> String {
+    syllable
+        .chars()
+        .map(|c| match c {
+            'ā' | 'á' | 'ǎ' | 'à' => 'a',
+            'ē' | 'é' | 'ě' | 'è' => 'e',
+            'ī' | 'í' | 'ǐ' | 'ì' => 'i',
+            'ō' | 'ó' | 'ǒ' | 'ò' => 'o',
+            'ū' | 'ú' | 'ǔ' | 'ù' => 'u',
+            'ǖ' | 'ǘ' | 'ǚ' | 'ǜ' | 'ü' => 'v',
+            'ń' | 'ň' | 'ǹ' => 'n',
+            'ḿ' => 'm',
+            _ => c,
+        })
+        .collect()
+}
@@ -0,0 +1,117 @@
+use super::{StandardTokenizer, Tokenizer};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Han,
+    Latin,
+    Kana,
+    Hangul,
+    Cyrillic,
+    Other,
+}
+
+/// Tokenizer detects script runs of input and dispatches each run to the configured tokenizer.
+/// Whitespaces, digits and punctuations belong to the run around them.
+pub struct ScriptTokenizer {
+    routes: HashMap<Script, Box<dyn Tokenizer>>,
+    fallback: Box<dyn Tokenizer>,
+}
+
+pub struct ScriptTokenizerBuilder {
+    inner: ScriptTokenizer,
+}
+
+impl Script {
+    /// Script of a char, None for common chars like whitespaces, digits and punctuations.
+    pub fn detect(c: char) -> Option<Script> {
+        let script = match c as u32 {
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F | 0x3007 => {
+                Script::Han
+            }
+            0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9D => Script::Kana,
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+            0x0400..=0x052F => Script::Cyrillic,
+            _ if c.is_ascii_alphabetic() => Script::Latin,
+            0x00C0..=0x024F | 0x1E00..=0x1EFF | 0xFF21..=0xFF3A | 0xFF41..=0xFF5A => Script::Latin,
+            _ if c.is_alphabetic() => Script::Other,
+            _ => return None,
+        };
+        Some(script)
+    }
+
+    /// Split input into runs of the same script.
+    pub fn runs(input: &str) -> Vec<(Script, &str)> {
+        let mut results: Vec<(Script, &str)> = vec![];
+        let mut current: Option<Script> = None;
+        let mut begin = 0;
+        for (i, c) in input.char_indices() {
+            if let Some(script) = Script::detect(c) {
+                match current {
+                    Some(prev) if prev != script => {
+                        results.push((prev, &input[begin..i]));
+                        begin = i;
+                        current = Some(script);
+                    }
+                    Some(_) => (),
+                    None => current = Some(script),
+                }
+            }
+        }
+        if begin < input.len() {
+            results.push((current.unwrap_or(Script::Other), &input[begin..]));
+        }
+        results
+    }
+}
+
+impl Default for ScriptTokenizer {
+    fn default() -> ScriptTokenizer {
+        ScriptTokenizer {
+            routes: HashMap::new(),
+            fallback: Box::new(StandardTokenizer),
+        }
+    }
+}
+
+impl Tokenizer for ScriptTokenizer {
+    fn tokenize<'a>(&self, input: &'a str) -> Vec<&'a str> {
+        let mut results = vec![];
+        for (script, run) in Script::runs(input) {
+            let tokenizer = self.routes.get(&script).unwrap_or(&self.fallback);
+            results.extend(tokenizer.tokenize(run));
+        }
+        results
+    }
+}
+
+impl ScriptTokenizer {
+    pub fn builder() -> ScriptTokenizerBuilder {
+        ScriptTokenizerBuilder {
+            inner: ScriptTokenizer::default(),
+        }
+    }
+}
+
+impl ScriptTokenizerBuilder {
+    pub fn route<T>(mut self, script: Script, tokenizer: T) -> Self
+    where
+        T: 'static + Tokenizer,
+    {
+        self.inner.routes.insert(script, Box::new(tokenizer));
+        self
+    }
+
+    /// Tokenizer of the scripts without route, `StandardTokenizer` by default.
+    pub fn fallback<T>(mut self, tokenizer: T) -> Self
+    where
+        T: 'static + Tokenizer,
+    {
+        self.inner.fallback = Box::new(tokenizer);
+        self
+    }
+
+    pub fn build(self) -> ScriptTokenizer {
+        self.inner
+    }
+}
@@ -0,0 +1,24 @@
+use super::Tokenizer;
+
+/// Splits text into words of letters and digits, eg: latin text.
+#[derive(Default)]
+pub struct StandardTokenizer;
+
+impl Tokenizer for StandardTokenizer {
+    fn tokenize<'a>(&self, input: &'a str) -> Vec<&'a str> {
+        input
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|it| !it.is_empty())
+            .collect()
+    }
+}
+
+/// Splits text by whitespace, the query tokenizer of searchers without any.
+#[derive(Default)]
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokenize<'a>(&self, input: &'a str) -> Vec<&'a str> {
+        input.split_whitespace().collect()
+    }
+}
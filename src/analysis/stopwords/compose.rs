@@ -0,0 +1,75 @@
+use super::{StopWords, StopWordsSet};
+
+/// A word is a stop word if any of the inner lists contains it.
+#[derive(Default)]
+pub struct StopWordsUnion {
+    inner: Vec<Box<dyn StopWords>>,
+}
+
+/// Adjust a base list: extra words are always stopped, kept words never.
+pub struct StopWordsOverride<S>
+where
+    S: StopWords,
+{
+    base: S,
+    stop: StopWordsSet,
+    keep: StopWordsSet,
+}
+
+impl StopWordsUnion {
+    pub fn with<S>(mut self, stopwords: S) -> Self
+    where
+        S: 'static + StopWords,
+    {
+        self.inner.push(Box::new(stopwords));
+        self
+    }
+}
+
+impl StopWords for StopWordsUnion {
+    fn contains(&self, word: &str) -> bool {
+        self.inner.iter().any(|it| it.contains(word))
+    }
+}
+
+impl<S> StopWordsOverride<S>
+where
+    S: StopWords,
+{
+    pub fn new(base: S) -> StopWordsOverride<S> {
+        StopWordsOverride {
+            base,
+            stop: Default::default(),
+            keep: Default::default(),
+        }
+    }
+
+    pub fn stop<A>(mut self, word: A) -> Self
+    where
+        A: Into<String>,
+    {
+        self.stop.insert(word);
+        self
+    }
+
+    pub fn keep<A>(mut self, word: A) -> Self
+    where
+        A: Into<String>,
+    {
+        self.keep.insert(word);
+        self
+    }
+}
+
+impl<S> StopWords for StopWordsOverride<S>
+where
+    S: StopWords,
+{
+    fn contains(&self, word: &str) -> bool {
+        if self.keep.contains(word) {
+            false
+        } else {
+            self.stop.contains(word) || self.base.contains(word)
+        }
+    }
+}
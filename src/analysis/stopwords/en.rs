@@ -0,0 +1,50 @@
+use super::StopWords;
+use std::collections::HashSet;
+
+lazy_static! {
+    static ref EN_STOP_WORDS: HashSet<&'static str> = {
+        let mut m = HashSet::new();
+        m.insert("a");
+        m.insert("an");
+        m.insert("and");
+        m.insert("are");
+        m.insert("as");
+        m.insert("at");
+        m.insert("be");
+        m.insert("but");
+        m.insert("by");
+        m.insert("for");
+        m.insert("if");
+        m.insert("in");
+        m.insert("into");
+        m.insert("is");
+        m.insert("it");
+        m.insert("no");
+        m.insert("not");
+        m.insert("of");
+        m.insert("on");
+        m.insert("or");
+        m.insert("such");
+        m.insert("that");
+        m.insert("the");
+        m.insert("their");
+        m.insert("then");
+        m.insert("there");
+        m.insert("these");
+        m.insert("they");
+        m.insert("this");
+        m.insert("to");
+        m.insert("was");
+        m.insert("will");
+        m.insert("with");
+        m
+    };
+}
+
+pub struct StopWordsEN;
+
+impl StopWords for StopWordsEN {
+    fn contains(&self, word: &str) -> bool {
+        EN_STOP_WORDS.contains(word) || EN_STOP_WORDS.contains(word.to_lowercase().as_str())
+    }
+}
@@ -1,7 +1,13 @@
 mod cn;
+mod compose;
+mod en;
+mod set;
 
-pub trait StopWords {
+pub trait StopWords: Send + Sync {
     fn contains(&self, word: &str) -> bool;
 }
 
 pub use cn::StopWordsCN;
+pub use compose::{StopWordsOverride, StopWordsUnion};
+pub use en::StopWordsEN;
+pub use set::StopWordsSet;
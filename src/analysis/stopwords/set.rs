@@ -0,0 +1,69 @@
+use super::StopWords;
+use crate::spi::Result;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::path::Path;
+
+/// In-memory stop words, can be loaded from a file with one word per line.
+#[derive(Default, Debug, Clone)]
+pub struct StopWordsSet {
+    words: HashSet<String>,
+}
+
+impl StopWordsSet {
+    pub fn open<P>(path: P) -> Result<StopWordsSet>
+    where
+        P: AsRef<Path>,
+    {
+        let mut result = StopWordsSet::default();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            let word = line.trim();
+            if word.is_empty() || word.starts_with('#') {
+                continue;
+            }
+            result.insert(word);
+        }
+        Ok(result)
+    }
+
+    pub fn insert<A>(&mut self, word: A)
+    where
+        A: Into<String>,
+    {
+        self.words.insert(word.into());
+    }
+
+    pub fn remove(&mut self, word: &str) -> bool {
+        self.words.remove(word)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+impl<A> From<Vec<A>> for StopWordsSet
+where
+    A: Into<String>,
+{
+    fn from(words: Vec<A>) -> StopWordsSet {
+        let mut result = StopWordsSet::default();
+        for it in words {
+            result.insert(it);
+        }
+        result
+    }
+}
+
+impl StopWords for StopWordsSet {
+    fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+}
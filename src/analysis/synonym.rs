@@ -0,0 +1,189 @@
+use super::{Token, TokenFilter, TOKEN_TYPE_SYNONYM};
+use crate::spi::Result;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// Synonym filter loads rules in Solr format:
+///
+/// ```text
+/// # equivalent synonyms, `expand` decides whether each one maps to all or to the first.
+/// 北京, 北平, 京城
+/// # explicit mappings, multi-word entries are separated by whitespace.
+/// 北大 => 北京 大学
+/// ```
+///
+/// Replacements are emitted as a token graph: every alternative starts at the position of the
+/// matched tokens and the last token of a shorter alternative spans the remaining positions.
+#[derive(Default)]
+pub struct SynonymFilter {
+    rules: HashMap<Vec<String>, Vec<Vec<String>>>,
+    max_len: usize,
+}
+
+impl SynonymFilter {
+    pub fn open<P>(path: P, expand: bool) -> Result<SynonymFilter>
+    where
+        P: AsRef<Path>,
+    {
+        let mut f = File::open(path)?;
+        let mut s = String::new();
+        f.read_to_string(&mut s)?;
+        Self::parse(&s, expand)
+    }
+
+    pub fn parse(input: &str, expand: bool) -> Result<SynonymFilter> {
+        let mut filter = SynonymFilter::default();
+        for (n, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let sides = split_unescaped(line, "=>", false);
+            match sides.len() {
+                1 => {
+                    let words = Self::parse_words(&sides[0]);
+                    if expand {
+                        for from in words.iter() {
+                            filter.add(from.clone(), words.clone());
+                        }
+                    } else if let Some(first) = words.first() {
+                        for from in words.iter() {
+                            filter.add(from.clone(), vec![first.clone()]);
+                        }
+                    }
+                }
+                2 => {
+                    let targets = Self::parse_words(&sides[1]);
+                    for from in Self::parse_words(&sides[0]) {
+                        filter.add(from, targets.clone());
+                    }
+                }
+                _ => return Err(format!("invalid synonym rule at line {}!", n + 1).into()),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Map a token sequence to its replacements, merged with existing rules.
+    pub fn add(&mut self, from: Vec<String>, to: Vec<Vec<String>>) {
+        if from.is_empty() {
+            return;
+        }
+        if from.len() > self.max_len {
+            self.max_len = from.len();
+        }
+        let exist = self.rules.entry(from).or_default();
+        for it in to {
+            if !it.is_empty() && !exist.contains(&it) {
+                exist.push(it);
+            }
+        }
+    }
+
+    fn parse_words(input: &str) -> Vec<Vec<String>> {
+        let mut results = vec![];
+        for it in split_unescaped(input, ",", true) {
+            let words: Vec<String> = it.split_whitespace().map(|w| w.to_string()).collect();
+            if !words.is_empty() {
+                results.push(words);
+            }
+        }
+        results
+    }
+
+    fn lookup(&self, tokens: &[Token], start: usize) -> Option<(usize, &Vec<Vec<String>>)> {
+        let mut key: Vec<String> = vec![];
+        let mut found = None;
+        for i in start..tokens.len() {
+            if key.len() >= self.max_len {
+                break;
+            }
+            if i > start {
+                let prev = &tokens[i - 1];
+                if tokens[i].position != prev.position + prev.span {
+                    break;
+                }
+            }
+            key.push(tokens[i].text.clone());
+            if let Some(to) = self.rules.get(&key) {
+                found = Some((key.len(), to));
+            }
+        }
+        found
+    }
+}
+
+impl TokenFilter for SynonymFilter {
+    fn filter(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut results = vec![];
+        let mut shift: i64 = 0;
+        let mut i = 0;
+        while i < tokens.len() {
+            let first = &tokens[i];
+            let start = first.position as i64 + shift;
+            match self.lookup(&tokens, i) {
+                Some((n, alternatives)) => {
+                    let last = &tokens[i + n - 1];
+                    let covered = (last.position + last.span - first.position) as i64;
+                    let end = alternatives.iter().map(|it| it.len()).max().unwrap() as i64;
+                    for words in alternatives.iter() {
+                        let l = words.len();
+                        for (j, word) in words.iter().enumerate() {
+                            let kind = if n == 1 && l == 1 && *word == first.text {
+                                first.kind
+                            } else {
+                                TOKEN_TYPE_SYNONYM
+                            };
+                            let mut token = Token::new(word.clone(), (start + j as i64) as u32)
+                                .offsets(first.start, last.end)
+                                .kind(kind);
+                            if j == l - 1 {
+                                token.span = (end - l as i64 + 1) as u32;
+                            }
+                            results.push(token);
+                        }
+                    }
+                    shift += end - covered;
+                    i += n;
+                }
+                None => {
+                    let mut token = first.clone();
+                    token.position = start as u32;
+                    results.push(token);
+                    i += 1;
+                }
+            }
+        }
+        results.sort_by_key(|it| it.position);
+        results
+    }
+}
+
+#[inline]
+fn split_unescaped(input: &str, sep: &str, unescape: bool) -> Vec<String> {
+    let mut results = vec![];
+    let mut cur = String::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        if rest.starts_with('\\') && rest.len() > 1 {
+            let c = rest[1..].chars().next().unwrap();
+            if !unescape {
+                cur.push('\\');
+            }
+            cur.push(c);
+            rest = &rest[1 + c.len_utf8()..];
+        } else if rest.starts_with(sep) {
+            results.push(cur.trim().to_string());
+            cur = String::new();
+            rest = &rest[sep.len()..];
+        } else {
+            let c = rest.chars().next().unwrap();
+            cur.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    results.push(cur.trim().to_string());
+    results
+}
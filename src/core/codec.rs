@@ -0,0 +1,132 @@
+use crate::io::Writer;
+use crate::spi::Result;
+
+const MAGIC: u32 = 0x4A45_5453;
+const FOOTER_MAGIC: u32 = !MAGIC;
+const FOOTER_SIZE: usize = 8;
+
+pub(crate) const VERSION_START: u32 = 1;
+pub(crate) const VERSION_CURRENT: u32 = VERSION_START;
+
+pub(crate) const CODEC_COMMIT: &str = "JetsCommit";
+pub(crate) const CODEC_SEGMENT: &str = "JetsSegment";
+pub(crate) const CODEC_DOC_VALUES: &str = "JetsDocValues";
+
+/// Writer keeping the position and the CRC32C of all bytes written.
+pub(crate) struct ChecksumWriter<W>
+where
+    W: Writer,
+{
+    inner: W,
+    crc: u32,
+    position: u64,
+}
+
+impl<W> ChecksumWriter<W>
+where
+    W: Writer,
+{
+    pub(crate) fn new(inner: W) -> ChecksumWriter<W> {
+        ChecksumWriter {
+            inner,
+            crc: 0,
+            position: 0,
+        }
+    }
+
+    pub(crate) fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Write header: magic, codec name, format version.
+    pub(crate) fn write_header(&mut self, codec: &str) {
+        self.put_u32(MAGIC);
+        self.put_u8(codec.len() as u8);
+        self.put_slice(codec.as_bytes());
+        self.put_u32(VERSION_CURRENT);
+    }
+
+    /// Write footer: footer magic, then the CRC32C of everything before the CRC itself.
+    pub(crate) fn finish(mut self) -> W {
+        self.put_u32(FOOTER_MAGIC);
+        let crc = self.crc;
+        self.inner.put_u32(crc);
+        self.inner
+    }
+}
+
+impl<W> Writer for ChecksumWriter<W>
+where
+    W: Writer,
+{
+    fn put_slice<B>(&mut self, input: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        let b = input.as_ref();
+        self.crc = crc32c::crc32c_append(self.crc, b);
+        self.position += b.len() as u64;
+        self.inner.put_slice(b);
+    }
+
+    fn put_u8(&mut self, input: u8) {
+        self.put_slice([input]);
+    }
+}
+
+/// Check header and footer of a file, returns the version and the range of its body.
+/// The checksum is verified when `verify` is set, it reads the whole file.
+pub(crate) fn check(
+    data: &[u8],
+    codec: &str,
+    verify: bool,
+) -> Result<(u32, std::ops::Range<usize>)> {
+    let header = 4 + 1 + codec.len() + 4;
+    if data.len() < header + FOOTER_SIZE {
+        return Err(corrupt(codec, "file truncated"));
+    }
+    if read_u32(data, 0) != MAGIC {
+        return Err(corrupt(codec, "invalid magic"));
+    }
+    if &data[5..5 + codec.len()] != codec.as_bytes() || data[4] as usize != codec.len() {
+        return Err(corrupt(codec, "codec mismatch"));
+    }
+    let version = read_u32(data, header - 4);
+    if version < VERSION_START || version > VERSION_CURRENT {
+        return Err(format!(
+            "unsupported version: {} version {}, supported {} to {}",
+            codec, version, VERSION_START, VERSION_CURRENT
+        )
+        .into());
+    }
+    let end = data.len() - FOOTER_SIZE;
+    if read_u32(data, end) != FOOTER_MAGIC {
+        return Err(corrupt(codec, "invalid footer magic"));
+    }
+    if verify {
+        let actual = crc32c::crc32c(&data[..data.len() - 4]);
+        let expected = read_u32(data, data.len() - 4);
+        if actual != expected {
+            return Err(corrupt(
+                codec,
+                &format!(
+                    "checksum mismatch, actual={:08x}, expected={:08x}",
+                    actual, expected
+                ),
+            ));
+        }
+    }
+    Ok((version, header..end))
+}
+
+#[inline]
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    let mut b = [0u8; 4];
+    b.copy_from_slice(&data[offset..offset + 4]);
+    u32::from_be_bytes(b)
+}
+
+#[inline]
+fn corrupt(codec: &str, reason: &str) -> Box<dyn std::error::Error> {
+    format!("corrupt index: {}, {}", codec, reason).into()
+}
@@ -0,0 +1,237 @@
+use super::codec::{self, ChecksumWriter, CODEC_COMMIT};
+use super::index::get_segment_path;
+use super::metadata::MetadataManager;
+use super::snapshot::{self, CHECKPOINT_PREFIX};
+use crate::io::{FileWriter, Writer};
+use crate::spi::Result;
+use bytes::{Buf, Bytes};
+use std::fs::{self, File};
+use std::path::Path;
+
+const PREFIX: &str = "segments_";
+const PENDING_PREFIX: &str = "pending_segments_";
+// metadata file of indexes written before commit points.
+const LEGACY_METADATA: &str = "METADATA";
+
+/// A commit point `segments_N`: generation, live segments, the last generation of the write-ahead
+/// log covered and metadata of an index, with the dictionary of its tokenizer.
+/// It is written to a pending file, fsynced, then renamed, so a crash never leaves it torn.
+pub(crate) struct CommitPoint {
+    generation: u64,
+    segments: Vec<u32>,
+    wal_generation: u64,
+    metadata: MetadataManager,
+    // generations of newer commit points skipped as corrupt.
+    skipped: Vec<u64>,
+}
+
+impl CommitPoint {
+    /// Read the latest valid commit point of dir, corrupt ones are skipped for older ones.
+    /// Files of skipped commits are kept, and segments written later never reuse their ids.
+    pub(crate) fn latest(dir: &str) -> Result<Option<CommitPoint>> {
+        let mut generations = list(dir, PREFIX)?;
+        generations.sort();
+        let mut skipped = vec![];
+        let mut failed = None;
+        for generation in generations.into_iter().rev() {
+            match Self::read(dir, generation) {
+                Ok(mut commit) => {
+                    if !skipped.is_empty() {
+                        commit.metadata.reserve_segments(max_segment(dir)? + 1);
+                    }
+                    commit.skipped = skipped;
+                    return Ok(Some(commit));
+                }
+                Err(e) => {
+                    warn!("skip commit {}: {}", file_name(generation), e);
+                    skipped.push(generation);
+                    failed = Some(e);
+                }
+            }
+        }
+        match failed {
+            Some(e) => Err(e),
+            None if Path::new(dir).join(LEGACY_METADATA).exists() => {
+                Err("unsupported version: index without commit points, it must be rebuilt".into())
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn read(dir: &str, generation: u64) -> Result<CommitPoint> {
+        let all = fs::read(Path::new(dir).join(file_name(generation)))?;
+        let (_, body) = codec::check(&all, CODEC_COMMIT, true)?;
+        let mut bf = Bytes::copy_from_slice(&all[body]);
+        if bf.get_u64() != generation {
+            return Err("corrupt index: commit generation mismatch".into());
+        }
+        let n = bf.get_u32();
+        let mut segments = vec![];
+        for _ in 0..n {
+            segments.push(bf.get_u32());
+        }
+        let wal_generation = bf.get_u64();
+        let metadata = MetadataManager::decode(&mut bf)?;
+        Ok(CommitPoint {
+            generation,
+            segments,
+            wal_generation,
+            metadata,
+            skipped: vec![],
+        })
+    }
+
+    /// Write a commit point durably, older commit points are removed after unless pinned by a
+    /// snapshot.
+    pub(crate) fn write(
+        dir: &str,
+        generation: u64,
+        segments: &[u32],
+        wal_generation: u64,
+        metadata: &MetadataManager,
+    ) -> Result<()> {
+        let pending = Path::new(dir).join(format!("{}{}", PENDING_PREFIX, generation));
+        let mut writer = ChecksumWriter::new(FileWriter::open(&pending)?);
+        writer.write_header(CODEC_COMMIT);
+        writer.put_u64(generation);
+        writer.put_u32(segments.len() as u32);
+        for it in segments {
+            writer.put_u32(*it);
+        }
+        writer.put_u64(wal_generation);
+        metadata.write(&mut writer);
+        writer.finish().sync()?;
+        fs::rename(&pending, Path::new(dir).join(file_name(generation)))?;
+        File::open(dir)?.sync_all()?;
+        let pinned = snapshot::pinned(dir);
+        for it in list(dir, PREFIX)? {
+            if it < generation && !pinned.contains(&it) {
+                fs::remove_file(Path::new(dir).join(file_name(it)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove pending commit points and segment files not referenced, left by a crashed flush,
+    /// and checkpoints of snapshots when none is alive. Segment files are kept when a newer
+    /// commit point is skipped, as they may be referenced by it.
+    pub(crate) fn clean(&self, dir: &str) -> Result<()> {
+        for it in list(dir, PENDING_PREFIX)? {
+            fs::remove_file(Path::new(dir).join(format!("{}{}", PENDING_PREFIX, it)))?;
+        }
+        if !self.skipped.is_empty() {
+            warn!(
+                "keep unreferenced files of {}: newer commits {:?} are skipped",
+                dir, self.skipped
+            );
+            return Ok(());
+        }
+        let pinned = snapshot::pinned(dir);
+        let mut referenced = self.segments.clone();
+        for it in pinned.iter() {
+            referenced.extend(Self::read(dir, *it)?.segments);
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let checkpoint = path
+                .file_name()
+                .and_then(|it| it.to_str())
+                .is_some_and(|it| it.starts_with(CHECKPOINT_PREFIX));
+            if checkpoint && pinned.is_empty() {
+                info!("remove checkpoint {:?}", path);
+                fs::remove_dir_all(path)?;
+                continue;
+            }
+            let segment = path
+                .file_stem()
+                .and_then(|it| it.to_str())
+                .and_then(|it| it.strip_prefix("_segment_"))
+                .and_then(|it| it.parse::<u32>().ok());
+            if let Some(segment) = segment {
+                if !referenced.contains(&segment) {
+                    info!("remove unreferenced file {:?}", path);
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The largest generation in dir, including the skipped ones, so a new commit point is
+    /// newer than all of them.
+    pub(crate) fn last_generation(&self) -> u64 {
+        self.skipped
+            .iter()
+            .copied()
+            .fold(self.generation, std::cmp::max)
+    }
+
+    pub(crate) fn segments(&self) -> &[u32] {
+        &self.segments
+    }
+
+    /// Records of the write-ahead log up to this generation are committed.
+    pub(crate) fn wal_generation(&self) -> u64 {
+        self.wal_generation
+    }
+
+    /// Names of the commit point file then files of its segments.
+    pub(crate) fn files(&self, dir: &str) -> Vec<String> {
+        let mut files = vec![file_name(self.generation)];
+        for it in self.segments.iter() {
+            for ext in &["index", "dv"] {
+                let path = get_segment_path(dir, *it, ext);
+                if path.exists() {
+                    files.push(path.file_name().unwrap().to_str().unwrap().to_string());
+                }
+            }
+        }
+        files
+    }
+
+    pub(crate) fn into_metadata(self) -> MetadataManager {
+        self.metadata
+    }
+}
+
+#[inline]
+fn file_name(generation: u64) -> String {
+    format!("{}{}", PREFIX, generation)
+}
+
+/// The largest id of segment files in dir.
+fn max_segment(dir: &str) -> Result<u32> {
+    let mut max = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some(n) = path
+            .file_stem()
+            .and_then(|it| it.to_str())
+            .and_then(|it| it.strip_prefix("_segment_"))
+            .and_then(|it| it.parse::<u32>().ok())
+        {
+            max = max.max(n);
+        }
+    }
+    Ok(max)
+}
+
+/// Generations of files with prefix in dir.
+fn list(dir: &str, prefix: &str) -> Result<Vec<u64>> {
+    let mut generations = vec![];
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        if let Some(n) = name
+            .to_str()
+            .and_then(|it| it.strip_prefix(prefix))
+            .and_then(|it| it.parse::<u64>().ok())
+        {
+            generations.push(n);
+        }
+    }
+    Ok(generations)
+}
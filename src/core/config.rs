@@ -0,0 +1,42 @@
+use super::wal::SyncPolicy;
+
+/// Options of an `IndexWriter`.
+#[derive(Debug, Clone, Default)]
+pub struct IndexWriterConfig {
+    wal: Option<SyncPolicy>,
+    ram_buffer_size: Option<usize>,
+    max_buffered_docs: Option<usize>,
+}
+
+impl IndexWriterConfig {
+    /// Log pushed documents ahead so they survive a crash before flush, disabled by default.
+    pub fn wal(mut self, policy: SyncPolicy) -> Self {
+        self.wal = Some(policy);
+        self
+    }
+
+    /// Flush when buffered documents take about n bytes of memory, disabled by default so
+    /// documents are only flushed by `IndexWriter::flush`.
+    pub fn ram_buffer_size(mut self, n: Option<usize>) -> Self {
+        self.ram_buffer_size = n;
+        self
+    }
+
+    /// Flush when n documents are buffered, disabled by default.
+    pub fn max_buffered_docs(mut self, n: Option<usize>) -> Self {
+        self.max_buffered_docs = n;
+        self
+    }
+
+    pub fn get_wal(&self) -> Option<SyncPolicy> {
+        self.wal
+    }
+
+    pub fn get_ram_buffer_size(&self) -> Option<usize> {
+        self.ram_buffer_size
+    }
+
+    pub fn get_max_buffered_docs(&self) -> Option<usize> {
+        self.max_buffered_docs
+    }
+}
@@ -1,17 +1,24 @@
 use super::spi::{Readable, Writeable};
 use crate::spi::Result;
+use crate::utils::{get_v32, put_v32};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::cmp::Ordering;
 use std::collections::{HashMap, LinkedList};
 use std::fmt;
 
 pub const FIELD_TYPE_TEXT: u8 = 1;
+pub const FIELD_TYPE_LONG: u8 = 2;
+pub const FIELD_TYPE_DOUBLE: u8 = 3;
 
 pub const FLAG_NOT_STORED: u8 = 0x01;
 pub const FLAG_TOKENIZED: u8 = 0x01 << 1;
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord)]
+/// Values are ordered text, long then double, doubles by IEEE 754 total order.
+#[derive(Debug)]
 pub enum DocValue {
     Text(String),
+    Long(i64),
+    Double(f64),
 }
 
 impl<A> From<A> for DocValue
@@ -23,18 +30,37 @@ where
     }
 }
 
-impl fmt::Display for DocValue {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            DocValue::Text(s) => write!(f, "{}", s),
+impl PartialEq for DocValue {
+    fn eq(&self, other: &DocValue) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DocValue {}
+
+impl PartialOrd for DocValue {
+    fn partial_cmp(&self, other: &DocValue) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DocValue {
+    fn cmp(&self, other: &DocValue) -> Ordering {
+        match (self, other) {
+            (DocValue::Text(a), DocValue::Text(b)) => a.cmp(b),
+            (DocValue::Long(a), DocValue::Long(b)) => a.cmp(b),
+            (DocValue::Double(a), DocValue::Double(b)) => a.total_cmp(b),
+            _ => self.get_type().cmp(&other.get_type()),
         }
     }
 }
 
-impl AsRef<[u8]> for DocValue {
-    fn as_ref(&self) -> &[u8] {
+impl fmt::Display for DocValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Text(s) => s.as_ref(),
+            DocValue::Text(s) => write!(f, "{}", s),
+            DocValue::Long(n) => write!(f, "{}", n),
+            DocValue::Double(n) => write!(f, "{}", n),
         }
     }
 }
@@ -46,6 +72,17 @@ impl DocValue {
                 Ok(s) => Ok(DocValue::Text(s)),
                 Err(e) => Err(Box::new(e)),
             },
+            FIELD_TYPE_LONG | FIELD_TYPE_DOUBLE => {
+                if raw.len() != 8 {
+                    return Err("invalid numeric field value".into());
+                }
+                let n = Bytes::from(raw).get_u64();
+                if field_type == FIELD_TYPE_LONG {
+                    Ok(DocValue::Long(n as i64))
+                } else {
+                    Ok(DocValue::Double(f64::from_bits(n)))
+                }
+            }
             _ => Err("invalid field type".into()),
         }
     }
@@ -53,12 +90,25 @@ impl DocValue {
     pub fn get_type(&self) -> u8 {
         match self {
             Self::Text(_) => FIELD_TYPE_TEXT,
+            Self::Long(_) => FIELD_TYPE_LONG,
+            Self::Double(_) => FIELD_TYPE_DOUBLE,
         }
     }
 
     pub fn bytes(&self) -> Vec<u8> {
         match self {
             DocValue::Text(s) => Vec::from(s.as_bytes()),
+            DocValue::Long(n) => n.to_be_bytes().to_vec(),
+            DocValue::Double(n) => n.to_bits().to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Numeric value of a long or double field.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DocValue::Text(_) => None,
+            DocValue::Long(n) => Some(*n as f64),
+            DocValue::Double(n) => Some(*n),
         }
     }
 }
@@ -123,6 +173,10 @@ impl Field {
     pub fn get_value(&self) -> &DocValue {
         &self.value
     }
+
+    pub fn get_flag(&self) -> u8 {
+        self.flag
+    }
 }
 
 impl Document {
@@ -165,3 +219,35 @@ impl Document {
         self.fields.is_empty()
     }
 }
+
+/// Compact binary form of a document, the id is not kept.
+impl Writeable for Document {
+    fn write_to(&self, bf: &mut BytesMut) -> Result<()> {
+        put_v32(bf, self.fields.len() as u32)?;
+        for it in self.fields.iter() {
+            put_v32(bf, it.name.len() as u32)?;
+            bf.put_slice(it.name.as_bytes());
+            bf.put_u8(it.flag);
+            bf.put_u8(it.value.get_type());
+            let value = it.value.bytes();
+            put_v32(bf, value.len() as u32)?;
+            bf.put_slice(&value);
+        }
+        Ok(())
+    }
+}
+
+impl Readable for Document {
+    fn read_from(&mut self, bf: &mut Bytes) -> Result<()> {
+        for _ in 0..get_v32(bf)? {
+            let n = get_v32(bf)? as usize;
+            let name = String::from_utf8(bf.split_to(n).to_vec())?;
+            let flag = bf.get_u8();
+            let kind = bf.get_u8();
+            let n = get_v32(bf)? as usize;
+            let value = DocValue::decode(kind, bf.split_to(n).to_vec())?;
+            self.push(Field::new(name, value, flag));
+        }
+        Ok(())
+    }
+}
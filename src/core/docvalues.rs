@@ -0,0 +1,315 @@
+use super::codec::{self, ChecksumWriter, CODEC_DOC_VALUES};
+use super::doc::DocValue;
+use crate::io::Writer;
+use crate::spi::Result;
+use crate::utils::{get_v32, put_v32, PackedInts, FOR};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+const COLUMN_SORTED: u8 = 1;
+const COLUMN_LONG: u8 = 2;
+const COLUMN_DOUBLE: u8 = 3;
+
+const PRESENCE_ALL: u8 = 0;
+const PRESENCE_BITMAP: u8 = 1;
+const PRESENCE_SPARSE: u8 = 2;
+
+// columns with less than 1/8 of documents keep a doc id list instead of a bitmap.
+const SPARSE_RATIO: usize = 8;
+
+const SIGN_BIT: u64 = 1 << 63;
+
+/// Column of a field in one segment, values are random accessed by segment-local doc id.
+pub(crate) enum Column {
+    Sorted(SortedColumn),
+    Numeric(NumericColumn),
+}
+
+/// Documents having a value: all of them, a bitmap (dense), or a sorted doc id list (sparse).
+/// Values of a dense column are indexed by doc id, of a sparse one by rank in the list.
+enum Presence {
+    All,
+    Bitmap(Bytes),
+    Sparse(Vec<u32>),
+}
+
+/// Text values are dictionary encoded: each document keeps the ordinal of its value
+/// in the sorted dictionary.
+pub(crate) struct SortedColumn {
+    dict: Vec<String>,
+    presence: Presence,
+    ords: PackedInts,
+}
+
+/// Long values are stored with the sign bit flipped so they are ordered as unsigned,
+/// double values as raw bits.
+pub(crate) struct NumericColumn {
+    double: bool,
+    presence: Presence,
+    values: PackedInts,
+}
+
+/// Doc values of a segment, addressed by field id then segment-local doc id.
+#[derive(Default)]
+pub(crate) struct SegmentDocValues {
+    max_doc: u32,
+    columns: HashMap<u32, Column>,
+}
+
+enum PendingColumn {
+    Sorted(Vec<(u32, String)>),
+    Numeric(bool, Vec<(u32, u64)>),
+}
+
+pub(crate) struct DocValuesWriter {
+    max_doc: u32,
+    columns: BTreeMap<u32, PendingColumn>,
+}
+
+impl Presence {
+    fn index(&self, doc: u32) -> Option<usize> {
+        let i = doc as usize;
+        match self {
+            Presence::All => Some(i),
+            Presence::Bitmap(bits) => match bits.get(i / 8) {
+                Some(b) if b & (1 << (i % 8)) != 0 => Some(i),
+                _ => None,
+            },
+            Presence::Sparse(docs) => docs.binary_search(&doc).ok(),
+        }
+    }
+
+    fn decode(bf: &mut Bytes, max_doc: usize) -> Result<Presence> {
+        match bf.get_u8() {
+            PRESENCE_ALL => Ok(Presence::All),
+            PRESENCE_BITMAP => Ok(Presence::Bitmap(bf.split_to(max_doc.div_ceil(8)))),
+            PRESENCE_SPARSE => {
+                let n = get_v32(bf)? as usize;
+                let docs: Vec<u32> = FOR::decode(bf)?.iter().take(n).collect();
+                Ok(Presence::Sparse(docs))
+            }
+            _ => Err("invalid doc values presence".into()),
+        }
+    }
+
+    /// Encode presence of docs (sorted), then the values to be packed in the same layout.
+    fn encode(bf: &mut BytesMut, max_doc: u32, values: Vec<(u32, u64)>) -> Result<Vec<u64>> {
+        let n = values.len();
+        if n == max_doc as usize {
+            bf.put_u8(PRESENCE_ALL);
+            Ok(values.into_iter().map(|(_, v)| v).collect())
+        } else if n * SPARSE_RATIO < max_doc as usize {
+            bf.put_u8(PRESENCE_SPARSE);
+            put_v32(bf, n as u32)?;
+            let docs: Vec<u32> = values.iter().map(|(doc, _)| *doc).collect();
+            FOR::from(&docs).write_to(bf)?;
+            Ok(values.into_iter().map(|(_, v)| v).collect())
+        } else {
+            bf.put_u8(PRESENCE_BITMAP);
+            let mut bits = vec![0u8; (max_doc as usize).div_ceil(8)];
+            // holes take the first value, so they cost nothing in packed blocks.
+            let mut packed = vec![values[0].1; max_doc as usize];
+            for (doc, v) in values {
+                let i = doc as usize;
+                bits[i / 8] |= 1 << (i % 8);
+                packed[i] = v;
+            }
+            bf.put_slice(&bits);
+            Ok(packed)
+        }
+    }
+}
+
+impl SortedColumn {
+    pub(crate) fn ord(&self, doc: u32) -> Option<u32> {
+        self.presence
+            .index(doc)
+            .and_then(|i| self.ords.get(i))
+            .map(|n| n as u32)
+    }
+
+    pub(crate) fn lookup(&self, ord: u32) -> &str {
+        &self.dict[ord as usize]
+    }
+
+    pub(crate) fn values(&self) -> usize {
+        self.dict.len()
+    }
+}
+
+impl NumericColumn {
+    pub(crate) fn get(&self, doc: u32) -> Option<DocValue> {
+        let raw = self.presence.index(doc).and_then(|i| self.values.get(i))?;
+        if self.double {
+            Some(DocValue::Double(f64::from_bits(raw)))
+        } else {
+            Some(DocValue::Long((raw ^ SIGN_BIT) as i64))
+        }
+    }
+}
+
+impl Column {
+    /// Value of document, None for text columns.
+    pub(crate) fn value(&self, doc: u32) -> Option<DocValue> {
+        match self {
+            Column::Numeric(c) => c.get(doc),
+            Column::Sorted(_) => None,
+        }
+    }
+
+    /// Value of document as number, None for text columns.
+    pub(crate) fn number(&self, doc: u32) -> Option<f64> {
+        self.value(doc).and_then(|v| v.as_f64())
+    }
+
+    pub(crate) fn long(&self, doc: u32) -> Option<i64> {
+        match self {
+            Column::Numeric(c) => match c.get(doc) {
+                Some(DocValue::Long(n)) => Some(n),
+                Some(DocValue::Double(n)) => Some(n as i64),
+                _ => None,
+            },
+            Column::Sorted(_) => None,
+        }
+    }
+}
+
+impl SegmentDocValues {
+    pub(crate) fn open<P>(path: P) -> Result<SegmentDocValues>
+    where
+        P: AsRef<Path>,
+    {
+        let mut all = vec![];
+        File::open(path)?.read_to_end(&mut all)?;
+        let (_, body) = codec::check(&all, CODEC_DOC_VALUES, true)?;
+        let mut bf = Bytes::copy_from_slice(&all[body]);
+        let max_doc = bf.get_u32();
+        let mut columns = HashMap::new();
+        for _ in 0..bf.get_u32() {
+            let field = bf.get_u32();
+            let column = match bf.get_u8() {
+                COLUMN_SORTED => {
+                    let mut dict = vec![];
+                    for _ in 0..get_v32(&mut bf)? {
+                        let n = get_v32(&mut bf)? as usize;
+                        dict.push(String::from_utf8(bf.split_to(n).to_vec())?);
+                    }
+                    let presence = Presence::decode(&mut bf, max_doc as usize)?;
+                    let ords = PackedInts::decode(&mut bf)?;
+                    Column::Sorted(SortedColumn {
+                        dict,
+                        presence,
+                        ords,
+                    })
+                }
+                kind @ COLUMN_LONG | kind @ COLUMN_DOUBLE => {
+                    let presence = Presence::decode(&mut bf, max_doc as usize)?;
+                    let values = PackedInts::decode(&mut bf)?;
+                    Column::Numeric(NumericColumn {
+                        double: kind == COLUMN_DOUBLE,
+                        presence,
+                        values,
+                    })
+                }
+                _ => return Err("invalid doc values column".into()),
+            };
+            columns.insert(field, column);
+        }
+        Ok(SegmentDocValues { max_doc, columns })
+    }
+
+    pub(crate) fn max_doc(&self) -> u32 {
+        self.max_doc
+    }
+
+    pub(crate) fn column(&self, field: u32) -> Option<&Column> {
+        self.columns.get(&field)
+    }
+}
+
+impl DocValuesWriter {
+    pub(crate) fn new(max_doc: u32) -> DocValuesWriter {
+        DocValuesWriter {
+            max_doc,
+            columns: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, field: u32, doc: u32, value: &DocValue) {
+        let pending = self.columns.entry(field).or_insert_with(|| match value {
+            DocValue::Text(_) => PendingColumn::Sorted(vec![]),
+            DocValue::Long(_) => PendingColumn::Numeric(false, vec![]),
+            DocValue::Double(_) => PendingColumn::Numeric(true, vec![]),
+        });
+        match (pending, value) {
+            (PendingColumn::Sorted(values), DocValue::Text(s)) => values.push((doc, s.clone())),
+            (PendingColumn::Numeric(false, values), DocValue::Long(n)) => {
+                values.push((doc, *n as u64 ^ SIGN_BIT))
+            }
+            (PendingColumn::Numeric(true, values), DocValue::Double(n)) => {
+                values.push((doc, n.to_bits()))
+            }
+            // field types are checked by metadata already.
+            _ => (),
+        }
+    }
+
+    /// Write columns with header and footer, returns the inner writer to be flushed.
+    pub(crate) fn write<W>(self, writer: W) -> Result<W>
+    where
+        W: Writer,
+    {
+        let mut writer = ChecksumWriter::new(writer);
+        writer.write_header(CODEC_DOC_VALUES);
+        writer.put_u32(self.max_doc);
+        writer.put_u32(self.columns.len() as u32);
+        for (field, pending) in self.columns.into_iter() {
+            let mut bf = BytesMut::new();
+            bf.put_u32(field);
+            match pending {
+                PendingColumn::Sorted(values) => {
+                    bf.put_u8(COLUMN_SORTED);
+                    Self::write_sorted(&mut bf, self.max_doc, values)?;
+                }
+                PendingColumn::Numeric(double, values) => {
+                    bf.put_u8(if double { COLUMN_DOUBLE } else { COLUMN_LONG });
+                    let values = Presence::encode(&mut bf, self.max_doc, first_values(values))?;
+                    PackedInts::new(&values).write_to(&mut bf)?;
+                }
+            }
+            writer.put_slice(&bf[..]);
+        }
+        Ok(writer.finish())
+    }
+
+    fn write_sorted(bf: &mut BytesMut, max_doc: u32, values: Vec<(u32, String)>) -> Result<()> {
+        let dict: Vec<&String> = values
+            .iter()
+            .map(|(_, v)| v)
+            .collect::<BTreeSet<&String>>()
+            .into_iter()
+            .collect();
+        put_v32(bf, dict.len() as u32)?;
+        for it in dict.iter() {
+            put_v32(bf, it.len() as u32)?;
+            bf.put_slice(it.as_bytes());
+        }
+        let ords: Vec<(u32, u64)> = values
+            .iter()
+            .map(|(doc, v)| (*doc, dict.binary_search(&v).unwrap() as u64))
+            .collect();
+        let ords = Presence::encode(bf, max_doc, first_values(ords))?;
+        PackedInts::new(&ords).write_to(bf)
+    }
+}
+
+/// Sort values by doc id, the first value wins for multi-valued fields.
+#[inline]
+fn first_values(mut values: Vec<(u32, u64)>) -> Vec<(u32, u64)> {
+    values.sort_by_key(|(doc, _)| *doc);
+    values.dedup_by_key(|(doc, _)| *doc);
+    values
+}
@@ -1,209 +1,645 @@
+use super::codec::{self, ChecksumWriter, CODEC_SEGMENT};
+use super::commit::CommitPoint;
+use super::config::IndexWriterConfig;
 use super::doc::{DocValue, Document, Field};
 use super::doc::{FLAG_NOT_STORED, FLAG_TOKENIZED};
+use super::docvalues::{Column, DocValuesWriter, SegmentDocValues};
 use super::metadata::*;
 use super::misc::DocID;
+use super::snapshot::Snapshot;
+use super::stats::FlushStats;
 use super::store::DocValueStore;
-use crate::analysis::{StopWords, StopWordsCN, Tokenizer};
+use super::wal::WriteAheadLog;
+use crate::analysis::{AnalyzedToken, Analyzer, JiebaTokenizer, Tokenizer};
 use crate::io::{FileWriter, Writer};
 use crate::spi::Result;
 use crate::utils::fst::*;
-use crate::utils::Stack;
-use bytes::{Buf, Bytes};
-use glob::glob;
+use crate::utils::{put_v32, read_v64, FOR};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use memmap::Mmap;
 use multimap::MultiMap;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use std::fs::{self, File};
 use std::mem;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 
 pub struct IndexWriter<A>
 where
     A: Tokenizer,
 {
     dir: String,
-    amounts: u64,
-    metadata: MetadataManager,
-    store: DocValueStore,
+    amounts: AtomicU64,
+    commits: Mutex<Commits>,
+    metadata: Mutex<MetadataManager>,
+    store: Arc<DocValueStore>,
+    // buffers not taken by a pushing thread.
+    buffers: Mutex<Vec<DocumentsWriter>>,
+    // held shared by pushes, exclusive by full flushes so every buffer is idle.
+    gate: RwLock<()>,
+    // documents pushed or replayed but not flushed yet, changed under the lock of wal.
+    pending: AtomicU64,
+    tokenizer: Arc<A>,
+    analyzers: HashMap<String, Analyzer>,
+    default_analyzer: Analyzer,
+    wal: Mutex<Option<WriteAheadLog>>,
+    config: IndexWriterConfig,
+    stats: Mutex<FlushStats>,
+}
+
+/// Documents buffered by one thread at a time, flushed as a segment of its own.
+#[derive(Default)]
+struct DocumentsWriter {
     values: MultiMap<u32, (DocValue, u32, u8)>,
-    tokenizer: A,
-    sequence: AtomicU32,
+    docs: u32,
+    bytes: usize,
+}
+
+#[derive(Default)]
+struct Commits {
+    generation: u64,
+    segments: Vec<u32>,
+    // segments flushed but not committed yet.
+    uncommitted: bool,
+    // generation of the write-ahead log records not committed yet.
+    logged: u64,
+    // documents replayed from the log are not committed yet.
+    replayed: bool,
+    // segments opened by near-real-time readers, shared with the next one.
+    opened: HashMap<String, Arc<Segment>>,
 }
 
 pub struct IndexReader {
+    dir: String,
     metadata: MetadataManager,
-    segments: HashMap<String, Segment>,
-    store: DocValueStore,
+    segments: HashMap<String, Arc<Segment>>,
+    store: Arc<DocValueStore>,
+    // restored from the dictionary stored with the index, if any.
+    tokenizer: Option<Arc<dyn Tokenizer>>,
 }
 
-type SegmentFST = FST<Vec<u32>, OutputsU32s>;
-
+/// Segment file: header, u32 segment id, then for each field its postings followed by its FST, then the
+/// directory of FSTs, a u64 directory offset and the footer. Each posting list is a v32 size then
+/// FOR encoded doc ids, the FST of a field maps a term to the offset of its postings.
 struct Segment {
     id: u32,
-    inner: HashMap<u32, SegmentFST>,
+    data: Mmap,
+    // nodes and root address of the FST of each field, parsed on open.
+    fields: HashMap<u32, (Range<usize>, usize)>,
+    doc_values: SegmentDocValues,
+    max_doc: u32,
 }
 
 impl Segment {
+    /// Open a segment, only its header and footer are checked, see `check_integrity`.
     fn open<P>(path: P) -> Result<Segment>
     where
         P: AsRef<Path>,
     {
+        let dv_path = path.as_ref().with_extension("dv");
+        let has_doc_values = dv_path.exists();
+        let doc_values = if has_doc_values {
+            SegmentDocValues::open(dv_path)?
+        } else {
+            SegmentDocValues::default()
+        };
         let f = File::open(path)?;
-        let mut reader = BufReader::new(f);
-        // TODO: read one by one.
-        let mut all = vec![];
-        let _ = reader.read_to_end(&mut all)?;
-        let mut bf = Bytes::from(all);
-        let segment_id = bf.get_u32();
-        let mut inner: HashMap<u32, SegmentFST> = HashMap::new();
-        while !bf.is_empty() {
+        // only the directory is read here, pages of FSTs and postings are loaded on lookup.
+        // SAFETY: segment files are immutable once committed, they are written to a new file
+        // and only removed when no commit point refers to them.
+        let data = unsafe { Mmap::map(&f)? };
+        let (_, body) = codec::check(&data, CODEC_SEGMENT, false)?;
+        if body.len() < 12 {
+            return Err("corrupt index: segment too short".into());
+        }
+        let segment_id = Bytes::copy_from_slice(&data[body.start..body.start + 4]).get_u32();
+        let tail = body.end - 8;
+        let offset = Bytes::copy_from_slice(&data[tail..body.end]).get_u64() as usize;
+        if offset < body.start + 4 || offset > tail - 4 {
+            return Err("corrupt index: bad segment directory offset".into());
+        }
+        let mut bf = Bytes::copy_from_slice(&data[offset..tail]);
+        let n = bf.get_u32() as usize;
+        // each entry: u32 field, u64 offset, u64 size.
+        if bf.len() < n * 20 {
+            return Err("corrupt index: segment directory truncated".into());
+        }
+        let mut fields = HashMap::new();
+        for _ in 0..n {
             let f_index = bf.get_u32();
-            let fst = FST::decoder(OutputsU32s, CodecVecU32OverFOR).decode(&mut bf)?;
-            inner.insert(f_index, fst);
+            let start = bf.get_u64() as usize;
+            let end = match start.checked_add(bf.get_u64() as usize) {
+                Some(end) if start >= body.start + 4 && end <= offset => end,
+                _ => return Err("corrupt index: bad segment fst range".into()),
+            };
+            let fst = MappedFST::open(&data[start..end])?;
+            fields.insert(f_index, (start..end - 8, fst.get_root()));
         }
+        let max_doc = if has_doc_values {
+            doc_values.max_doc()
+        } else {
+            count_postings(&data, body.start + 4, &fields)?
+        };
         Ok(Segment {
             id: segment_id,
-            inner,
+            data,
+            fields,
+            doc_values,
+            max_doc,
         })
     }
 
-    fn find<R>(&self, field: u32, key: R) -> Option<Vec<u64>>
+    /// Verify the checksum of the segment file, it reads the whole file.
+    fn check_integrity(&self) -> Result<()> {
+        codec::check(&self.data, CODEC_SEGMENT, true).map(|_| ())
+    }
+
+    /// Postings of key in field, None if not found. Errors if the segment is corrupt.
+    fn find<R>(&self, field: u32, key: R) -> Result<Option<Vec<u64>>>
     where
         R: AsRef<[u8]>,
     {
-        match self.inner.get(&field) {
-            Some(fst) => match fst.get(key) {
-                None => None,
-                Some(origin) => {
-                    let mut result = vec![];
-                    for id in origin {
-                        result.push(DocID::reformat(self.id, id));
-                    }
-                    Some(result)
-                }
-            },
-            None => None,
+        let (range, root) = match self.fields.get(&field) {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        // errors name the segment, so a corrupt one can be found.
+        let in_segment = |e| format!("{} in segment {}", e, self.id);
+        let fst = MappedFST::with_root(&self.data[range.clone()], *root);
+        let mut pos = match fst.get(key).map_err(in_segment)? {
+            Some(offset) => offset as usize,
+            None => return Ok(None),
+        };
+        let postings = read_v64(&self.data, &mut pos)
+            .and_then(|size| pos.checked_add(size as usize))
+            .and_then(|end| self.data.get(pos..end))
+            .ok_or_else(|| in_segment("corrupt index: bad segment postings range".into()))?;
+        let postings = FOR::decode_slice(postings, &mut 0).map_err(in_segment)?;
+        Ok(Some(
+            postings
+                .iter()
+                .map(|id| DocID::reformat(self.id, id))
+                .collect(),
+        ))
+    }
+}
+
+/// Tokenizer of the dictionary of a commit, see `Tokenizer::dictionary`. Unknown kinds of
+/// tokenizers are not restored.
+fn restore_tokenizer(dictionary: Option<(&str, &str)>) -> Result<Option<Arc<dyn Tokenizer>>> {
+    match dictionary {
+        Some((JiebaTokenizer::KIND, dictionary)) => {
+            Ok(Some(Arc::new(JiebaTokenizer::from_dictionary(dictionary)?)))
+        }
+        Some((kind, _)) => {
+            warn!("tokenizer {} is not restored, set it to searchers", kind);
+            Ok(None)
         }
+        None => Ok(None),
     }
 }
 
+/// Documents of a segment without doc values, by the largest doc id in the postings of its
+/// fields. Postings of a field are written right before its FST.
+fn count_postings(
+    data: &[u8],
+    start: usize,
+    fields: &HashMap<u32, (Range<usize>, usize)>,
+) -> Result<u32> {
+    let mut fsts: Vec<&Range<usize>> = fields.values().map(|(range, _)| range).collect();
+    fsts.sort_by_key(|range| range.start);
+    let mut max_doc = 0;
+    let mut pos = start;
+    for fst in fsts {
+        while pos < fst.start {
+            let postings = read_v64(data, &mut pos)
+                .and_then(|size| pos.checked_add(size as usize))
+                .filter(|end| *end <= fst.start)
+                .map(|end| &data[pos..end])
+                .ok_or("corrupt index: bad segment postings range")?;
+            pos += postings.len();
+            if let Some(last) = FOR::decode_slice(postings, &mut 0)?.iter().max() {
+                max_doc = max_doc.max(last + 1);
+            }
+        }
+        // skip the FST and its root address.
+        pos = fst.end + 8;
+    }
+    Ok(max_doc)
+}
+
 impl<A> IndexWriter<A>
 where
     A: Tokenizer,
 {
     pub fn open(path: &str, tokenizer: A) -> Result<IndexWriter<A>> {
-        let store = DocValueStore::open(&get_data_path(path))?;
-        let metadata_path = get_metadata_path(path);
-        let metadata = {
-            let p = Path::new(&metadata_path);
-            if !p.exists() {
-                Ok(MetadataManager::default())
-            } else {
-                MetadataManager::open(&metadata_path)
+        Self::open_with_config(path, tokenizer, IndexWriterConfig::default())
+    }
+
+    /// Open with options, documents left in the write-ahead log are pushed again.
+    /// A tokenizer without a custom dictionary loads the one stored with the index, otherwise
+    /// its dictionary is stored by the next commit. Errors if the stored dictionary is of
+    /// another kind of tokenizer.
+    pub fn open_with_config(
+        path: &str,
+        mut tokenizer: A,
+        config: IndexWriterConfig,
+    ) -> Result<IndexWriter<A>> {
+        let store = Arc::new(DocValueStore::open(&get_data_path(path))?);
+        let (commits, metadata) = match CommitPoint::latest(path)? {
+            Some(commit) => {
+                commit.clean(path)?;
+                let commits = Commits {
+                    generation: commit.last_generation(),
+                    segments: commit.segments().to_vec(),
+                    logged: commit.wal_generation() + 1,
+                    ..Default::default()
+                };
+                (commits, commit.into_metadata())
             }
-        }?;
-        Ok(IndexWriter {
+            None => {
+                let commits = Commits {
+                    logged: 1,
+                    ..Default::default()
+                };
+                (commits, MetadataManager::default())
+            }
+        };
+        let mut metadata = metadata;
+        match tokenizer.dictionary() {
+            Some(dictionary) => metadata.set_dictionary(tokenizer.kind(), dictionary),
+            None => {
+                if let Some((kind, dictionary)) = metadata.get_dictionary() {
+                    if kind != tokenizer.kind() {
+                        return Err(format!(
+                            "tokenizer mismatch: dictionary of {} stored, opened by {}",
+                            kind,
+                            tokenizer.kind()
+                        )
+                        .into());
+                    }
+                    tokenizer.load_dictionary(dictionary)?;
+                }
+            }
+        }
+        let logged = commits.logged;
+        let writer = IndexWriter {
             dir: path.to_string(),
-            amounts: 0,
-            metadata: metadata,
+            amounts: Default::default(),
+            commits: Mutex::new(commits),
+            metadata: Mutex::new(metadata),
             store,
-            values: Default::default(),
-            tokenizer,
-            sequence: Default::default(),
-        })
+            buffers: Default::default(),
+            gate: Default::default(),
+            pending: Default::default(),
+            tokenizer: Arc::new(tokenizer),
+            analyzers: HashMap::new(),
+            default_analyzer: Analyzer::default(),
+            wal: Default::default(),
+            config,
+            stats: Default::default(),
+        };
+        let wal_path = get_wal_path(path);
+        let mut dw = Some(DocumentsWriter::default());
+        let mut replayed = false;
+        // flushed by the same limits as pushes, committed once the whole log is flushed.
+        for doc in WriteAheadLog::replay(&wal_path, logged - 1)? {
+            // a document rejected by the fields can not be buffered, eg: logged by older versions.
+            let ids = match writer.resolve(&doc) {
+                Ok(ids) => ids,
+                Err(e) => {
+                    warn!("skip logged document of {:?}: {}", wal_path, e);
+                    continue;
+                }
+            };
+            let mut buffer = dw.take().unwrap_or_default();
+            writer.buffer(&mut buffer, doc, ids);
+            writer.pending.fetch_add(1, Ordering::SeqCst);
+            replayed = true;
+            dw = writer.maybe_flush(buffer)?;
+        }
+        writer.commits.lock().unwrap().replayed = replayed;
+        writer.buffers.lock().unwrap().extend(dw);
+        if let Some(policy) = writer.config.get_wal() {
+            *writer.wal.lock().unwrap() = Some(WriteAheadLog::open(&wal_path, policy, logged)?);
+        }
+        Ok(writer)
     }
 
-    pub fn push(&mut self, doc: Document) -> Result<()> {
-        for it in doc.fields {
-            let i = self
-                .metadata
-                .fields_mut()
-                .put(&it.name, it.value.get_type())?;
-            let field_id = self.sequence.fetch_add(1, Ordering::SeqCst);
-            self.values.insert(i, (it.value, field_id, it.flag));
+    /// Register the analyzer of a tokenized field, eg: stop words or pinyin variants.
+    pub fn set_analyzer<N>(&mut self, field: N, analyzer: Analyzer)
+    where
+        N: Into<String>,
+    {
+        self.analyzers.insert(field.into(), analyzer);
+    }
+
+    /// Explain how text of a tokenized field is analyzed, see `Analyzer::explain`.
+    pub fn analyze(&self, field: &str, text: &str) -> Vec<AnalyzedToken> {
+        self.analyzers
+            .get(field)
+            .unwrap_or(&self.default_analyzer)
+            .explain(self.tokenizer.as_ref(), text)
+    }
+
+    /// Push a document, it can be called from many threads. Each thread buffers documents in a
+    /// buffer of its own, which is flushed as a separate segment.
+    pub fn push(&self, doc: Document) -> Result<()> {
+        let _gate = self.gate.read().unwrap();
+        // validated before logged, a rejected document is never replayed.
+        let ids = self.resolve(&doc)?;
+        {
+            let mut wal = self.wal.lock().unwrap();
+            if let Some(wal) = wal.as_mut() {
+                wal.append(&doc)?;
+            }
+            self.pending.fetch_add(1, Ordering::SeqCst);
+        }
+        let mut dw = self.buffers.lock().unwrap().pop().unwrap_or_default();
+        self.buffer(&mut dw, doc, ids);
+        match self.maybe_flush(dw)? {
+            Some(dw) => {
+                self.buffers.lock().unwrap().push(dw);
+                Ok(())
+            }
+            None => self.commit(),
+        }
+    }
+
+    /// Flush when the RAM buffer or max buffered docs is reached, dw is returned if not flushed.
+    fn maybe_flush(&self, dw: DocumentsWriter) -> Result<Option<DocumentsWriter>> {
+        let ram_full = self
+            .config
+            .get_ram_buffer_size()
+            .is_some_and(|n| self.stats.lock().unwrap().get_buffered_bytes() >= n as u64);
+        let docs_full = self
+            .config
+            .get_max_buffered_docs()
+            .is_some_and(|n| dw.docs as usize >= n);
+        if ram_full {
+            self.flush_buffer(self.take_largest(dw), true)?;
+        } else if docs_full {
+            self.flush_buffer(dw, true)?;
+        } else {
+            return Ok(Some(dw));
+        }
+        Ok(None)
+    }
+
+    /// The field ids of a document, registered if new, rejected if a field changes its type.
+    fn resolve(&self, doc: &Document) -> Result<Vec<u32>> {
+        let mut metadata = self.metadata.lock().unwrap();
+        let mut ids = Vec::with_capacity(doc.fields.len());
+        for it in doc.fields.iter() {
+            let tokenized = it.flag & FLAG_TOKENIZED != 0;
+            ids.push(
+                metadata
+                    .fields_mut()
+                    .put(&it.name, it.value.get_type(), tokenized)?,
+            );
+        }
+        Ok(ids)
+    }
+
+    fn buffer(&self, dw: &mut DocumentsWriter, doc: Document, ids: Vec<u32>) {
+        let mut bytes = 0;
+        for (i, it) in ids.into_iter().zip(doc.fields) {
+            bytes += ram_usage(&it.value);
+            dw.values.insert(i, (it.value, dw.docs, it.flag));
+        }
+        dw.docs += 1;
+        dw.bytes += bytes;
+        self.amounts.fetch_add(1, Ordering::SeqCst);
+        self.stats.lock().unwrap().buffer(bytes);
+    }
+
+    /// The largest of dw and the idle buffers, the other one is left idle.
+    fn take_largest(&self, dw: DocumentsWriter) -> DocumentsWriter {
+        let mut buffers = self.buffers.lock().unwrap();
+        match buffers.iter().enumerate().max_by_key(|(_, it)| it.bytes) {
+            Some((i, largest)) if largest.bytes > dw.bytes => mem::replace(&mut buffers[i], dw),
+            _ => dw,
         }
-        self.amounts += 1;
-        Ok(())
     }
 
     pub fn counter(&self) -> u64 {
-        self.amounts
+        self.amounts.load(Ordering::SeqCst)
+    }
+
+    pub fn stats(&self) -> FlushStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Flush buffered documents of all threads and commit them.
+    pub fn flush(&self) -> Result<()> {
+        let _gate = self.gate.write().unwrap();
+        self.flush_all()?;
+        self.commit()
     }
 
-    pub fn flush(&mut self) -> Result<()> {
-        if self.values.is_empty() {
+    /// Pin files of the latest commit and take a checkpoint of stored fields, so a backup can
+    /// be copied while pushing goes on. Pins only hold in this process. See `Snapshot::backup_to`.
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        // no commit is written while pinning.
+        let _commits = self.commits.lock().unwrap();
+        let commit = CommitPoint::latest(&self.dir)?.ok_or("snapshot failed: no commit found")?;
+        Snapshot::new(&self.dir, &commit, &self.store)
+    }
+
+    /// Point-in-time reader of all flushed segments, including the ones not committed yet.
+    /// Buffered documents are flushed first, segments opened by previous readers are reused.
+    pub fn reader(&self) -> Result<IndexReader>
+    where
+        A: 'static,
+    {
+        let _gate = self.gate.write().unwrap();
+        self.flush_all()?;
+        let mut commits = self.commits.lock().unwrap();
+        let segments = open_segments(&self.dir, &commits.segments, &commits.opened)?;
+        commits.opened = segments.clone();
+        let metadata = self.metadata.lock().unwrap().clone();
+        let tokenizer = metadata
+            .get_dictionary()
+            .map(|_| self.tokenizer.clone() as Arc<dyn Tokenizer>);
+        Ok(IndexReader {
+            dir: self.dir.clone(),
+            metadata,
+            segments,
+            store: self.store.clone(),
+            tokenizer,
+        })
+    }
+
+    // called with the gate held exclusively.
+    fn flush_all(&self) -> Result<()> {
+        let mut buffers = mem::take(&mut *self.buffers.lock().unwrap()).into_iter();
+        while let Some(dw) = buffers.next() {
+            if let Err(e) = self.flush_buffer(dw, false) {
+                self.buffers.lock().unwrap().extend(buffers);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush dw as a segment, it is buffered again if failed so its documents are flushed by a
+    /// later flush.
+    fn flush_buffer(&self, dw: DocumentsWriter, auto: bool) -> Result<()> {
+        if dw.docs == 0 {
             return Ok(());
         }
-        let segment = self.metadata.next_segment();
-        let origin = mem::replace(&mut self.values, Default::default());
-        let path = format!("{}/_segment_{:08}.index", &self.dir, segment);
-        let mut writer = FileWriter::open(path)?;
+        let begin = Instant::now();
+        let (docs, bytes) = (dw.docs, dw.bytes);
+        let segment = match self.write_segment(&dw) {
+            Ok(segment) => segment,
+            Err(e) => {
+                self.buffers.lock().unwrap().push(dw);
+                return Err(e);
+            }
+        };
+        {
+            let mut commits = self.commits.lock().unwrap();
+            commits.segments.push(segment);
+            commits.uncommitted = true;
+        }
+        self.pending.fetch_sub(docs as u64, Ordering::SeqCst);
+        self.stats
+            .lock()
+            .unwrap()
+            .flushed(docs as u64, bytes as u64, begin.elapsed(), auto);
+        Ok(())
+    }
+
+    fn write_segment(&self, dw: &DocumentsWriter) -> Result<u32> {
+        let segment = self.metadata.lock().unwrap().next_segment();
+        let path = get_segment_path(&self.dir, segment, "index");
+        let mut writer = ChecksumWriter::new(FileWriter::open(path)?);
+        writer.write_header(CODEC_SEGMENT);
+        let mut doc_values = DocValuesWriter::new(dw.docs);
         writer.put_u32(segment);
-        for (findex, values) in origin.into_iter() {
+        let mut directory = vec![];
+        for (findex, values) in dw.values.iter_all() {
             for (dv, id, flag) in values.iter() {
                 let real_id = DocID::reformat(segment, *id);
                 if flag & FLAG_NOT_STORED == 0 {
-                    self.store.write(real_id, findex, dv)?;
+                    self.store.write(real_id, *findex, dv)?;
+                }
+                if flag & FLAG_TOKENIZED == 0 || dv.as_f64().is_some() {
+                    doc_values.add(*findex, *id, dv);
                 }
             }
-            let processed = self.process(values);
-            let mut stack: Stack<(String, u32)> = Stack::new();
-            let mut builder = FST::builder(OutputsU32s);
-            for (cur, id) in processed {
-                if let Some((prev, i)) = stack.pop() {
-                    if prev == cur {
-                        stack.push((prev, i));
-                    } else {
-                        let mut ids = vec![];
-                        ids.push(i);
-                        while let Some(top) = (&mut stack).pop() {
-                            ids.push(top.1);
-                        }
-                        builder = builder.push(prev, to_sorted_unique_ids(ids));
-                    }
+            let mut postings: Vec<(String, Vec<u32>)> = vec![];
+            for (term, id) in self.process(*findex, values) {
+                match postings.last_mut() {
+                    Some((last, ids)) if *last == term => ids.push(id),
+                    _ => postings.push((term, vec![id])),
                 }
-                stack.push((cur, id));
             }
+            let mut builder = FST::builder(OutputsU64);
+            for (term, ids) in postings {
+                let mut encoded = BytesMut::new();
+                FOR::from(to_sorted_unique_ids(ids)).write_to(&mut encoded)?;
+                let mut bf = BytesMut::new();
+                put_v32(&mut bf, encoded.len() as u32)?;
+                bf.put_slice(&encoded[..]);
+                builder = builder.push(term, writer.position());
+                writer.put_slice(&bf[..]);
+            }
+            // generate segment
+            let offset = writer.position();
+            let size = builder.build().save_mapped(&mut writer)? as u64;
+            directory.push((*findex, offset, size));
+        }
+        let offset = writer.position();
+        writer.put_u32(directory.len() as u32);
+        for (findex, offset, size) in directory {
+            writer.put_u32(findex);
+            writer.put_u64(offset);
+            writer.put_u64(size);
+        }
+        writer.put_u64(offset);
+        writer.finish().sync()?;
+        let path = get_segment_path(&self.dir, segment, "dv");
+        doc_values.write(FileWriter::open(path)?)?.sync()?;
+        Ok(segment)
+    }
 
-            if let Some((k, v)) = stack.pop() {
-                let mut ids = vec![];
-                ids.push(v);
-                while let Some(top) = stack.pop() {
-                    ids.push(top.1);
+    /// Publish flushed segments by a new commit point. With the log enabled or documents replayed
+    /// from it, only when every logged document is in one of them, so the log can be dropped.
+    fn commit(&self) -> Result<()> {
+        let mut wal = self.wal.lock().unwrap();
+        let pending = self.pending.load(Ordering::SeqCst);
+        let mut commits = self.commits.lock().unwrap();
+        if (wal.is_some() || commits.replayed) && pending > 0 {
+            return Ok(());
+        }
+        if !commits.uncommitted {
+            return Ok(());
+        }
+        // segment files are durable now.
+        commits.generation += 1;
+        // records of the log are covered when all of them are flushed.
+        let covered = if pending == 0 {
+            commits.logged
+        } else {
+            commits.logged - 1
+        };
+        {
+            let metadata = self.metadata.lock().unwrap();
+            CommitPoint::write(
+                &self.dir,
+                commits.generation,
+                &commits.segments,
+                covered,
+                &metadata,
+            )?;
+        }
+        commits.uncommitted = false;
+        if pending > 0 {
+            return Ok(());
+        }
+        // documents are committed, drop them from log.
+        commits.logged += 1;
+        commits.replayed = false;
+        match wal.as_mut() {
+            Some(wal) => wal.truncate(),
+            None => {
+                let wal_path = get_wal_path(&self.dir);
+                if wal_path.exists() {
+                    fs::remove_file(wal_path)?;
                 }
-                builder = builder.push(k, to_sorted_unique_ids(ids));
+                Ok(())
             }
-
-            writer.put_u32(findex);
-            // generate segment
-            let fst = builder.build();
-            fst.save(&mut writer, CodecVecU32OverFOR)?;
-            writer.flush()?;
         }
-        let mut metadata_writer = FileWriter::open(&get_metadata_path(&self.dir))?;
-        self.metadata.write(&mut metadata_writer)?;
-        metadata_writer.flush()?;
-        Ok(())
     }
 
     #[inline]
-    fn process(&self, values: Vec<(DocValue, u32, u8)>) -> Vec<(String, u32)> {
+    fn process(&self, findex: u32, values: &[(DocValue, u32, u8)]) -> Vec<(String, u32)> {
+        let analyzer = self
+            .metadata
+            .lock()
+            .unwrap()
+            .fields()
+            .get(findex)
+            .and_then(|info| self.analyzers.get(info.get_name()))
+            .unwrap_or(&self.default_analyzer);
         let mut results = vec![];
         for (v, id, flag) in values {
+            let id = *id;
             match v {
                 DocValue::Text(text) => {
                     if flag & FLAG_TOKENIZED != 0 {
-                        for word in self.tokenizer.tokenize(&text) {
-                            if !StopWordsCN.contains(word) {
-                                results.push((word.to_string(), id));
-                            }
+                        for token in analyzer.analyze(self.tokenizer.as_ref(), text) {
+                            results.push((token.text, id));
                         }
                     } else {
                         results.push((text.to_string(), id));
                     }
                 }
+                DocValue::Long(_) | DocValue::Double(_) => results.push((v.to_string(), id)),
             }
         }
         results.sort_by(|a, b| a.0.cmp(&b.0));
@@ -212,6 +648,8 @@ where
 }
 
 impl IndexReader {
+    /// Open the latest commit. Only headers and footers of segment files are checked, they are
+    /// not read whole, corruption is found by `check_integrity` or a failed lookup.
     pub fn open(path: &str) -> Result<IndexReader> {
         let db_path = get_data_path(path);
         let ok = {
@@ -221,41 +659,152 @@ impl IndexReader {
         if !ok {
             return Err("open index failed!".into());
         }
-        let mut segments = HashMap::new();
         let store = DocValueStore::open(&db_path)?;
-        let metadata_path = get_metadata_path(path);
-        let metadata = MetadataManager::open(&metadata_path)?;
-        for it in glob(&format!("{}/_segment_*.index", path))? {
-            let target: PathBuf = it?;
-            let k = target.file_name().unwrap().to_str().unwrap().to_string();
-            let segment = Segment::open(target)?;
-            segments.insert(k, segment);
-        }
+        let commit = CommitPoint::latest(path)?.ok_or("open index failed: no commit found")?;
+        // files not referenced by the commit are ignored.
+        let segments = open_segments(path, commit.segments(), &HashMap::new())?;
+        let metadata = commit.into_metadata();
+        let tokenizer = restore_tokenizer(metadata.get_dictionary())?;
         Ok(IndexReader {
+            dir: path.to_string(),
             metadata,
-            store,
+            store: Arc::new(store),
             segments,
+            tokenizer,
         })
     }
 
-    pub fn find<R>(&self, field: &str, value: R) -> Option<Vec<u64>>
+    /// Reader of the latest commit, None unless it has segments this one does not have. A
+    /// commit missing segments of this reader is older, eg: than a reader of `IndexWriter::reader`
+    /// seeing uncommitted segments. Segments are never rewritten, so the ones already open are
+    /// shared and only new segments are opened.
+    pub fn reopen(&self) -> Result<Option<IndexReader>> {
+        let commit =
+            CommitPoint::latest(&self.dir)?.ok_or("reopen index failed: no commit found")?;
+        let ids = commit.segments().to_vec();
+        let current = self.segment_ids();
+        if ids.len() <= current.len() || current.iter().any(|it| !ids.contains(it)) {
+            return Ok(None);
+        }
+        let segments = open_segments(&self.dir, &ids, &self.segments)?;
+        let metadata = commit.into_metadata();
+        let tokenizer = if metadata.get_dictionary() == self.metadata.get_dictionary() {
+            self.tokenizer.clone()
+        } else {
+            restore_tokenizer(metadata.get_dictionary())?
+        };
+        Ok(Some(IndexReader {
+            dir: self.dir.clone(),
+            metadata,
+            store: self.store.clone(),
+            segments,
+            tokenizer,
+        }))
+    }
+
+    /// Ids of the documents with value in field, empty if the field is unknown. Errors if a
+    /// segment is corrupt.
+    pub fn find<V>(&self, field: &str, value: V) -> Result<Vec<u64>>
     where
-        R: AsRef<[u8]>,
+        V: Into<DocValue>,
     {
-        match self.metadata.fields().search(field) {
-            Some(info) => {
-                let mut merge = vec![];
-                for (_k, segment) in self.segments.iter() {
-                    if let Some(mut found) = segment.find(info.get_id(), &value) {
-                        merge.append(&mut found);
-                    }
-                }
-                Some(merge)
+        let founds = self.find_segments(field, value)?;
+        Ok(founds
+            .into_iter()
+            .filter_map(|(_, found)| found)
+            .flatten()
+            .collect())
+    }
+
+    /// Lookup value in each segment having the field, with the postings found per segment.
+    pub(crate) fn find_segments<V>(
+        &self,
+        field: &str,
+        value: V,
+    ) -> Result<Vec<(u32, Option<Vec<u64>>)>>
+    where
+        V: Into<DocValue>,
+    {
+        // numeric values are indexed as their decimal text.
+        let key = value.into().to_string();
+        let info = match self.metadata.fields().search(field) {
+            Some(info) => info,
+            None => return Ok(vec![]),
+        };
+        self.segments
+            .values()
+            .filter(|segment| segment.fields.contains_key(&info.get_id()))
+            .map(|segment| Ok((segment.id, segment.find(info.get_id(), &key)?)))
+            .collect()
+    }
+
+    /// Lookup value in one segment.
+    pub(crate) fn find_in<V>(&self, segment: u32, field: &str, value: V) -> Result<Option<Vec<u64>>>
+    where
+        V: Into<DocValue>,
+    {
+        let info = match self.metadata.fields().search(field) {
+            Some(info) => info,
+            None => return Ok(None),
+        };
+        match self.segments.values().find(|it| it.id == segment) {
+            Some(found) => found.find(info.get_id(), value.into().to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// Verify checksums of all segment files, it reads them whole.
+    pub fn check_integrity(&self) -> Result<()> {
+        for it in self.segments.values() {
+            it.check_integrity()?;
+        }
+        Ok(())
+    }
+
+    /// Tokenizer of the dictionary stored with the index by its writer, None if it has none
+    /// or the tokenizer is not known, see `Tokenizer::kind`.
+    pub fn get_tokenizer(&self) -> Option<&dyn Tokenizer> {
+        self.tokenizer.as_deref()
+    }
+
+    /// Dictionary stored with the latest commit of dir, only the commit point is read. None
+    /// if the index is not created yet, errors if it is of another kind of tokenizer.
+    pub fn read_dictionary(dir: &str, kind: &str) -> Result<Option<String>> {
+        if !Path::new(dir).exists() {
+            return Ok(None);
+        }
+        let metadata = match CommitPoint::latest(dir)? {
+            Some(commit) => commit.into_metadata(),
+            None => return Ok(None),
+        };
+        match metadata.get_dictionary() {
+            Some((stored, dictionary)) if stored == kind => Ok(Some(dictionary.to_string())),
+            Some((stored, _)) => {
+                Err(format!("dictionary of {} stored, not {}", stored, kind).into())
             }
-            None => None,
+            None => Ok(None),
         }
     }
 
+    /// Number of documents in all segments, by the postings of segments without doc values.
+    pub fn num_docs(&self) -> u64 {
+        self.segments.values().map(|it| it.max_doc as u64).sum()
+    }
+
+    pub(crate) fn segment_ids(&self) -> Vec<u32> {
+        self.segments.values().map(|it| it.id).collect()
+    }
+
+    /// Doc values column of a field in segment, text fields only when not tokenized.
+    pub(crate) fn column(&self, segment: u32, field: &str) -> Option<&Column> {
+        let info = self.metadata.fields().search(field)?;
+        self.segments
+            .values()
+            .find(|it| it.id == segment)
+            .and_then(|it| it.doc_values.column(info.get_id()))
+    }
+
+    /// Stored fields of a document, flagged `FLAG_TOKENIZED` if the field was tokenized.
     pub fn document(&self, id: u64) -> Option<Document> {
         let fields = self.metadata.fields().list();
         if fields.len() < 1 {
@@ -264,7 +813,12 @@ impl IndexReader {
         let mut bu = Document::builder_with_id(id);
         for field in fields {
             if let Ok(Some(dv)) = self.store.get(id, field.get_id(), field.get_kind()) {
-                bu = bu.put(field.get_name(), dv, 0);
+                let flag = if field.is_tokenized() {
+                    FLAG_TOKENIZED
+                } else {
+                    0
+                };
+                bu = bu.put(field.get_name(), dv, flag);
             }
         }
         let doc = bu.build();
@@ -281,9 +835,43 @@ fn get_data_path(dir: &str) -> String {
     format!("{}/data", dir)
 }
 
+/// Estimated memory of a buffered value.
+#[inline]
+fn ram_usage(value: &DocValue) -> usize {
+    let payload = match value {
+        DocValue::Text(s) => s.capacity(),
+        DocValue::Long(_) | DocValue::Double(_) => 0,
+    };
+    mem::size_of::<(u32, (DocValue, u32, u8))>() + payload
+}
+
+/// Open segments by id, taking the ones found in opened.
+fn open_segments(
+    dir: &str,
+    ids: &[u32],
+    opened: &HashMap<String, Arc<Segment>>,
+) -> Result<HashMap<String, Arc<Segment>>> {
+    let mut segments = HashMap::new();
+    for id in ids {
+        let target = get_segment_path(dir, *id, "index");
+        let k = target.file_name().unwrap().to_str().unwrap().to_string();
+        let segment = match opened.get(&k) {
+            Some(it) => it.clone(),
+            None => Arc::new(Segment::open(target)?),
+        };
+        segments.insert(k, segment);
+    }
+    Ok(segments)
+}
+
+#[inline]
+fn get_wal_path(dir: &str) -> PathBuf {
+    Path::new(dir).join("wal.log")
+}
+
 #[inline]
-fn get_metadata_path(dir: &str) -> String {
-    format!("{}/METADATA", dir)
+pub(crate) fn get_segment_path(dir: &str, segment: u32, ext: &str) -> PathBuf {
+    Path::new(dir).join(format!("_segment_{:08}.{}", segment, ext))
 }
 
 #[inline]
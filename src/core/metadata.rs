@@ -2,15 +2,16 @@ use crate::io::Writer;
 use crate::spi::Result;
 use bytes::{Buf, BufMut, Bytes};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::prelude::*;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, RwLock};
 
+#[derive(Clone)]
 pub(crate) struct FieldInfo {
     id: u32,
     kind: u8,
     name: String,
+    // tokenized in any document.
+    tokenized: bool,
 }
 
 pub(crate) struct FieldInfoManager {
@@ -20,9 +21,10 @@ pub(crate) struct FieldInfoManager {
 
 #[derive(Default)]
 pub(crate) struct MetadataManager {
-    magic: u32,
     segments: AtomicU32,
     fields_manager: FieldInfoManager,
+    // kind of the tokenizer and its custom dictionary, see `Tokenizer::dictionary`.
+    dictionary: Option<(String, String)>,
 }
 
 impl FieldInfo {
@@ -37,6 +39,10 @@ impl FieldInfo {
     pub(crate) fn get_name(&self) -> &str {
         &self.name
     }
+
+    pub(crate) fn is_tokenized(&self) -> bool {
+        self.tokenized
+    }
 }
 
 impl Default for FieldInfoManager {
@@ -48,15 +54,26 @@ impl Default for FieldInfoManager {
     }
 }
 
+impl Clone for FieldInfoManager {
+    fn clone(&self) -> FieldInfoManager {
+        let m = self.fields_map.read().unwrap();
+        FieldInfoManager {
+            fields_map: Arc::new(RwLock::new(m.clone())),
+            fields: self.fields.clone(),
+        }
+    }
+}
+
 impl FieldInfoManager {
-    pub(crate) fn put(&mut self, name: &str, kind: u8) -> Result<u32> {
+    pub(crate) fn put(&mut self, name: &str, kind: u8, tokenized: bool) -> Result<u32> {
         let mut m = self.fields_map.write().unwrap();
         match m.get(name) {
             Some(n) => {
-                let info = &self.fields[*n as usize];
+                let info = &mut self.fields[*n as usize];
                 if info.kind != kind {
                     return Err("conflict field type!".into());
                 }
+                info.tokenized |= tokenized;
                 Ok(*n)
             }
             None => {
@@ -65,6 +82,7 @@ impl FieldInfoManager {
                     id: i,
                     name: name.to_string(),
                     kind: kind,
+                    tokenized,
                 });
                 m.insert(name.to_string(), i);
                 Ok(i)
@@ -89,42 +107,63 @@ impl FieldInfoManager {
     }
 }
 
+impl Clone for MetadataManager {
+    fn clone(&self) -> MetadataManager {
+        MetadataManager {
+            segments: AtomicU32::new(self.segments.load(Ordering::SeqCst)),
+            fields_manager: self.fields_manager.clone(),
+            dictionary: self.dictionary.clone(),
+        }
+    }
+}
+
 impl MetadataManager {
-    pub(crate) fn open(path: &str) -> Result<MetadataManager> {
-        let mut f = File::open(path)?;
-        let mut all = vec![];
-        f.read_to_end(&mut all)?;
-        let mut reader = Bytes::from(all);
-        let magic = reader.get_u32();
+    pub(crate) fn decode(reader: &mut Bytes) -> Result<MetadataManager> {
         let segment = reader.get_u32();
         let totals = reader.get_u32();
         let mut fm = FieldInfoManager::default();
         for _ in 0..totals {
             let kind = reader.get_u8();
+            let tokenized = reader.get_u8() != 0;
             let name_len = reader.get_u32();
             let name = String::from_utf8(reader.split_to(name_len as usize).to_vec())?;
-            fm.put(&name, kind)?;
+            fm.put(&name, kind, tokenized)?;
         }
+        let dictionary = match reader.get_u32() as usize {
+            0 => None,
+            n => {
+                let kind = String::from_utf8(reader.split_to(n).to_vec())?;
+                let n = reader.get_u32() as usize;
+                Some((kind, String::from_utf8(reader.split_to(n).to_vec())?))
+            }
+        };
         Ok(MetadataManager {
-            magic,
             segments: AtomicU32::new(segment),
             fields_manager: fm,
+            dictionary,
         })
     }
 
-    pub(crate) fn write(&mut self, writer: &mut impl Writer) -> Result<()> {
-        writer.put_u32(self.magic);
+    pub(crate) fn write(&self, writer: &mut impl Writer) {
         writer.put_u32(self.segments.load(Ordering::SeqCst));
         let _ = self.fields_manager.fields_map.write().unwrap();
         let fields = self.fields_manager.list();
         writer.put_u32(fields.len() as u32);
         for it in fields.iter() {
             writer.put_u8(it.kind);
+            writer.put_u8(it.tokenized as u8);
             let b = it.name.as_bytes();
             writer.put_u32(b.len() as u32);
             writer.put_slice(b);
         }
-        Ok(())
+        // an empty kind for none.
+        let (kind, dictionary) = self.get_dictionary().unwrap_or_default();
+        writer.put_u32(kind.len() as u32);
+        writer.put_slice(kind.as_bytes());
+        if !kind.is_empty() {
+            writer.put_u32(dictionary.len() as u32);
+            writer.put_slice(dictionary.as_bytes());
+        }
     }
 
     pub(crate) fn fields_mut(&mut self) -> &mut FieldInfoManager {
@@ -135,7 +174,23 @@ impl MetadataManager {
         &self.fields_manager
     }
 
+    /// Kind of the tokenizer and its dictionary.
+    pub(crate) fn get_dictionary(&self) -> Option<(&str, &str)> {
+        self.dictionary
+            .as_ref()
+            .map(|(kind, dictionary)| (kind.as_str(), dictionary.as_str()))
+    }
+
+    pub(crate) fn set_dictionary(&mut self, kind: &str, dictionary: String) {
+        self.dictionary = Some((kind.to_string(), dictionary));
+    }
+
     pub(crate) fn next_segment(&mut self) -> u32 {
         self.segments.fetch_add(1, Ordering::SeqCst)
     }
+
+    /// Make ids of next segments at least n.
+    pub(crate) fn reserve_segments(&mut self, n: u32) {
+        self.segments.fetch_max(n, Ordering::SeqCst);
+    }
 }
@@ -1,10 +1,22 @@
+mod codec;
+mod commit;
+mod config;
 mod doc;
+mod docvalues;
 mod index;
 mod metadata;
 mod misc;
+mod snapshot;
 mod spi;
+mod stats;
 mod store;
+mod wal;
 
+pub use config::IndexWriterConfig;
 pub use doc::{DocValue, Document, Field, FLAG_NOT_STORED, FLAG_TOKENIZED};
+pub(crate) use docvalues::Column;
 pub use index::{IndexReader, IndexWriter};
+pub use snapshot::{BackupStats, Snapshot};
+pub use stats::FlushStats;
 pub use store::DocValueStore;
+pub use wal::SyncPolicy;
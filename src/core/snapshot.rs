@@ -0,0 +1,230 @@
+use super::commit::CommitPoint;
+use super::store::DocValueStore;
+use crate::spi::Result;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+pub(crate) const CHECKPOINT_PREFIX: &str = "snapshot_";
+
+/// Trailing bytes compared to reuse a file in a backup: the CRC32C footer of segment files,
+/// the footer of block handles of RocksDB tables.
+const TAIL_SIZE: u64 = 64;
+
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    // generations of commits pinned by snapshots, by index directory. It is process-local,
+    // commits are only protected from writers of the same process.
+    static ref PINNED: Mutex<HashMap<PathBuf, Vec<u64>>> = Mutex::new(HashMap::new());
+}
+
+/// Generations of commits of dir pinned by live snapshots.
+pub(crate) fn pinned(dir: &str) -> Vec<u64> {
+    PINNED
+        .lock()
+        .unwrap()
+        .get(&pin_key(dir))
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Files of a commit and a checkpoint of the stored fields, which are not deleted until the
+/// snapshot is dropped. See `IndexWriter::snapshot`.
+///
+/// Pins are kept in memory: a writer of the index in another process does not see them and
+/// may delete the files of the commit.
+pub struct Snapshot {
+    dir: PathBuf,
+    generation: u64,
+    files: Vec<String>,
+    checkpoint: PathBuf,
+}
+
+/// Statistics of a backup.
+#[derive(Debug, Clone, Default)]
+pub struct BackupStats {
+    copied_files: u64,
+    reused_files: u64,
+    copied_bytes: u64,
+}
+
+impl Snapshot {
+    pub(crate) fn new(dir: &str, commit: &CommitPoint, store: &DocValueStore) -> Result<Snapshot> {
+        let key = pin_key(dir);
+        PINNED
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .push(commit.generation());
+        let mut snapshot = Snapshot {
+            dir: key,
+            generation: commit.generation(),
+            files: commit.files(dir),
+            checkpoint: PathBuf::new(),
+        };
+        let seq = SEQUENCE.fetch_add(1, Ordering::SeqCst);
+        let checkpoint = Path::new(dir).join(format!(
+            "{}{}_{}",
+            CHECKPOINT_PREFIX,
+            commit.generation(),
+            seq
+        ));
+        // dropping the snapshot unpins the commit if checkpoint fails.
+        store.checkpoint(&checkpoint)?;
+        snapshot.checkpoint = checkpoint;
+        Ok(snapshot)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Names of the commit point and segment files in the index directory.
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    /// Copy the snapshot to dir, which can be opened as a normal index. Segment files and
+    /// stored field tables already there, with the same length and footer, are skipped, so
+    /// backing up to the same dir again only copies what changed. Other files are replaced by
+    /// renames, the RocksDB CURRENT file and then the commit point last, and files of the
+    /// previous backup are removed after that: an interrupted backup keeps the previous one
+    /// until the next backup completes.
+    pub fn backup_to<P>(&self, dir: P) -> Result<BackupStats>
+    where
+        P: AsRef<Path>,
+    {
+        let target = dir.as_ref();
+        let data = target.join("data");
+        fs::create_dir_all(&data)?;
+        let mut stats = BackupStats::default();
+        let mut names = vec![];
+        for entry in fs::read_dir(&self.checkpoint)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                names.push(entry.file_name());
+            }
+        }
+        // tables of RocksDB are immutable, the others are rewritten, CURRENT names the manifest.
+        names.sort_by_key(|name| match name.to_str() {
+            Some(it) if it.ends_with(".sst") => 0,
+            Some("CURRENT") => 2,
+            _ => 1,
+        });
+        for name in names.iter() {
+            let from = self.checkpoint.join(name);
+            if name.to_str().is_some_and(|it| it.ends_with(".sst")) {
+                stats.copy(&from, &data.join(name), true)?;
+            } else {
+                stats.replace(&from, &data.join(name))?;
+            }
+        }
+        File::open(&data)?.sync_all()?;
+
+        let (commit, segments) = self.files.split_first().unwrap();
+        for name in segments {
+            stats.copy(&self.dir.join(name), &target.join(name), true)?;
+        }
+        stats.replace(&self.dir.join(commit), &target.join(commit))?;
+        File::open(target)?.sync_all()?;
+        remove_others(&data, |name| !names.iter().any(|it| it == name))?;
+        remove_others(target, |name| {
+            let name = name.to_str().unwrap_or_default();
+            (name.starts_with("segments_") || name.starts_with("_segment_"))
+                && !self.files.iter().any(|it| it == name)
+        })?;
+        info!(
+            "backup commit {} to {:?}: {:?}",
+            self.generation, target, stats
+        );
+        Ok(stats)
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        if self.checkpoint.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.checkpoint) {
+                warn!("remove checkpoint {:?} failed: {}", self.checkpoint, e);
+            }
+        }
+        let mut pinned = PINNED.lock().unwrap();
+        if let Some(generations) = pinned.get_mut(&self.dir) {
+            if let Some(i) = generations.iter().position(|it| *it == self.generation) {
+                generations.remove(i);
+            }
+            if generations.is_empty() {
+                pinned.remove(&self.dir);
+            }
+        }
+    }
+}
+
+impl BackupStats {
+    fn copy(&mut self, from: &Path, to: &Path, immutable: bool) -> Result<()> {
+        if immutable && to.exists() && tail(from)? == tail(to)? {
+            self.reused_files += 1;
+            return Ok(());
+        }
+        self.copied_bytes += fs::copy(from, to)?;
+        File::open(to)?.sync_all()?;
+        self.copied_files += 1;
+        Ok(())
+    }
+
+    /// Copy to a pending file renamed to `to`, so `to` is either the old file or the new one.
+    fn replace(&mut self, from: &Path, to: &Path) -> Result<()> {
+        let name = to.file_name().unwrap_or_default().to_string_lossy();
+        let pending = to.with_file_name(format!("pending_{}", name));
+        self.copy(from, &pending, false)?;
+        fs::rename(&pending, to)?;
+        Ok(())
+    }
+
+    pub fn get_copied_files(&self) -> u64 {
+        self.copied_files
+    }
+
+    /// Number of files skipped as they are in the backup already.
+    pub fn get_reused_files(&self) -> u64 {
+        self.reused_files
+    }
+
+    pub fn get_copied_bytes(&self) -> u64 {
+        self.copied_bytes
+    }
+}
+
+/// Length and trailing bytes of a file.
+fn tail(path: &Path) -> Result<(u64, Vec<u8>)> {
+    let mut f = File::open(path)?;
+    let len = f.metadata()?.len();
+    let mut bs = vec![];
+    f.seek(SeekFrom::Start(len.saturating_sub(TAIL_SIZE)))?;
+    f.read_to_end(&mut bs)?;
+    Ok((len, bs))
+}
+
+#[inline]
+fn pin_key(dir: &str) -> PathBuf {
+    fs::canonicalize(dir).unwrap_or_else(|_| PathBuf::from(dir))
+}
+
+/// Remove files of dir matched by f.
+fn remove_others<F>(dir: &Path, f: F) -> Result<()>
+where
+    F: Fn(&std::ffi::OsStr) -> bool,
+{
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && f(&entry.file_name()) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+/// Statistics of the flushes of an `IndexWriter`.
+#[derive(Debug, Clone, Default)]
+pub struct FlushStats {
+    flushes: u64,
+    auto_flushes: u64,
+    flushed_docs: u64,
+    flushed_bytes: u64,
+    elapsed: Duration,
+    last_elapsed: Duration,
+    buffered_docs: u64,
+    buffered_bytes: u64,
+}
+
+impl FlushStats {
+    pub(crate) fn buffer(&mut self, bytes: usize) {
+        self.buffered_docs += 1;
+        self.buffered_bytes += bytes as u64;
+    }
+
+    pub(crate) fn flushed(&mut self, docs: u64, bytes: u64, elapsed: Duration, auto: bool) {
+        self.flushes += 1;
+        if auto {
+            self.auto_flushes += 1;
+        }
+        self.flushed_docs += docs;
+        self.flushed_bytes += bytes;
+        self.elapsed += elapsed;
+        self.last_elapsed = elapsed;
+        self.buffered_docs -= docs;
+        self.buffered_bytes -= bytes;
+    }
+
+    /// Number of segments flushed.
+    pub fn get_flushes(&self) -> u64 {
+        self.flushes
+    }
+
+    /// Number of flushes triggered by the RAM buffer size or max buffered docs.
+    pub fn get_auto_flushes(&self) -> u64 {
+        self.auto_flushes
+    }
+
+    pub fn get_flushed_docs(&self) -> u64 {
+        self.flushed_docs
+    }
+
+    /// Estimated memory of the flushed documents while buffered.
+    pub fn get_flushed_bytes(&self) -> u64 {
+        self.flushed_bytes
+    }
+
+    /// Total time spent in flushes.
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn get_last_elapsed(&self) -> Duration {
+        self.last_elapsed
+    }
+
+    /// Number of documents waiting for the next flush.
+    pub fn get_buffered_docs(&self) -> u64 {
+        self.buffered_docs
+    }
+
+    /// Estimated memory of the documents waiting for the next flush.
+    pub fn get_buffered_bytes(&self) -> u64 {
+        self.buffered_bytes
+    }
+}
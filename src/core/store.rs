@@ -1,20 +1,42 @@
 use super::doc::DocValue;
 use crate::spi::Result;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
+use rocksdb::checkpoint::Checkpoint;
 use rocksdb::DB;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
 
 const ROW_KEY_METADATA: [u8; 1] = [0];
 
+lazy_static! {
+    // RocksDB allows one handle of a path per process, so stores of a path share it.
+    static ref OPENED: Mutex<HashMap<PathBuf, Weak<DB>>> = Mutex::new(HashMap::new());
+}
+
 pub struct DocValueStore {
-    db: DB,
+    db: Arc<DB>,
 }
 
 impl DocValueStore {
+    /// Open the store of path, the database is shared with other stores of the same path in
+    /// this process, eg: a writer and readers of one index.
     pub fn open(path: &str) -> Result<DocValueStore> {
-        match DB::open_default(path) {
-            Ok(db) => Ok(DocValueStore { db }),
-            Err(e) => Err(Box::new(e)),
+        let key = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        let mut opened = OPENED.lock().unwrap();
+        if let Some(db) = opened.get(&key).and_then(|it| it.upgrade()) {
+            return Ok(DocValueStore { db });
         }
+        let db = match DB::open_default(path) {
+            Ok(db) => Arc::new(db),
+            Err(e) => return Err(Box::new(e)),
+        };
+        opened.retain(|_, it| it.strong_count() > 0);
+        // the path exists after open.
+        let key = fs::canonicalize(path).unwrap_or(key);
+        opened.insert(key, Arc::downgrade(&db));
+        Ok(DocValueStore { db })
     }
 
     pub fn get(&self, id: u64, field: u32, field_type: u8) -> Result<Option<DocValue>> {
@@ -34,6 +56,12 @@ impl DocValueStore {
         }
     }
 
+    /// Create a checkpoint of the store in path, tables are hard linked when possible.
+    pub(crate) fn checkpoint(&self, path: &Path) -> Result<()> {
+        Checkpoint::new(&self.db)?.create_checkpoint(path)?;
+        Ok(())
+    }
+
     #[inline]
     fn to_row_key(id: u64, field: u32) -> [u8; 12] {
         let mut b: [u8; 12] = [0; 12];
@@ -0,0 +1,127 @@
+use super::doc::Document;
+use super::spi::{Readable, Writeable};
+use crate::spi::Result;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::fs::{self, File, OpenOptions};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const RECORD_HEADER_SIZE: usize = 16;
+
+/// When the write-ahead log is fsynced. Appends are written to the file before `push` returns,
+/// so documents survive a crash of the process whatever the policy, fsync guards against a
+/// crash of the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Fsync on every push.
+    Always,
+    /// Fsync on push when the last fsync is older than interval. There is no timer, pushes
+    /// before an idle period stay unsynced until the next push or commit.
+    Interval(Duration),
+    /// Leave it to the OS.
+    Never,
+}
+
+/// Log of documents pushed since the last commit. Each record is a u32 size, the u32 CRC32C of
+/// the rest, the u64 generation of the log, then the document in its compact binary form.
+/// The generation is increased on every truncate, a commit point records the last generation
+/// it covers, so records left by a crash before truncate are not replayed again.
+pub(crate) struct WriteAheadLog {
+    path: PathBuf,
+    file: File,
+    policy: SyncPolicy,
+    last_sync: Instant,
+    generation: u64,
+}
+
+impl WriteAheadLog {
+    pub(crate) fn open<P>(path: P, policy: SyncPolicy, generation: u64) -> Result<WriteAheadLog>
+    where
+        P: AsRef<Path>,
+    {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())?;
+        Ok(WriteAheadLog {
+            path: path.as_ref().to_path_buf(),
+            file,
+            policy,
+            last_sync: Instant::now(),
+            generation,
+        })
+    }
+
+    /// Read documents of log newer than generation committed, a torn record at tail left by a
+    /// crash is cut off.
+    pub(crate) fn replay<P>(path: P, committed: u64) -> Result<Vec<Document>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let all = fs::read(path)?;
+        let mut docs = vec![];
+        let mut pos = 0;
+        while all.len() - pos >= RECORD_HEADER_SIZE {
+            let mut header = Bytes::copy_from_slice(&all[pos..pos + RECORD_HEADER_SIZE]);
+            let size = header.get_u32() as usize;
+            let crc = header.get_u32();
+            let generation = header.get_u64();
+            let start = pos + RECORD_HEADER_SIZE;
+            if all.len() - start < size || crc32c::crc32c(&all[pos + 8..start + size]) != crc {
+                break;
+            }
+            if generation > committed {
+                let mut doc = Document::builder().build();
+                doc.read_from(&mut Bytes::copy_from_slice(&all[start..start + size]))?;
+                docs.push(doc);
+            }
+            pos = start + size;
+        }
+        if pos < all.len() {
+            warn!("cut off torn tail of {:?}: {} bytes", path, all.len() - pos);
+            let f = OpenOptions::new().write(true).open(path)?;
+            f.set_len(pos as u64)?;
+            f.sync_all()?;
+        }
+        Ok(docs)
+    }
+
+    pub(crate) fn append(&mut self, doc: &Document) -> Result<()> {
+        let mut payload = BytesMut::new();
+        doc.write_to(&mut payload)?;
+        let mut bf = BytesMut::with_capacity(RECORD_HEADER_SIZE + payload.len());
+        bf.put_u32(payload.len() as u32);
+        bf.put_u32(0);
+        bf.put_u64(self.generation);
+        bf.put_slice(&payload[..]);
+        let crc = crc32c::crc32c(&bf[8..]);
+        bf[4..8].copy_from_slice(&crc.to_be_bytes());
+        self.file.write_all(&bf[..])?;
+        let due = match self.policy {
+            SyncPolicy::Always => true,
+            SyncPolicy::Interval(interval) => self.last_sync.elapsed() >= interval,
+            SyncPolicy::Never => false,
+        };
+        if due {
+            self.file.sync_data()?;
+            self.last_sync = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Drop all records, called after their documents are committed.
+    pub(crate) fn truncate(&mut self) -> Result<()> {
+        // records appended after are newer than the committed ones, even if truncate fails.
+        self.generation += 1;
+        self.file.set_len(0)?;
+        self.file.sync_all()?;
+        self.last_sync = Instant::now();
+        debug!("truncate {:?}", self.path);
+        Ok(())
+    }
+}
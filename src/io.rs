@@ -64,6 +64,10 @@ pub trait Writer {
         self.put_slice(input.to_be_bytes());
     }
 
+    fn put_u64(&mut self, input: u64) {
+        self.put_slice(input.to_be_bytes());
+    }
+
     fn put_v32(&mut self, mut input: u32) {
         let mut b: Vec<u8> = vec![];
         while input & !0x7F != 0 {
@@ -157,6 +161,13 @@ impl FileWriter {
             Err(e) => Err(Box::new(e)),
         }
     }
+
+    /// Flush and fsync the file, so it is durable.
+    pub fn sync(&mut self) -> Result<()> {
+        self.flush()?;
+        self.inner.get_ref().sync_all()?;
+        Ok(())
+    }
 }
 
 impl Writer for FileWriter {
@@ -11,9 +11,9 @@ pub mod spi;
 pub mod utils;
 
 pub mod prelude {
-    pub use crate::analysis::{StopWords, Tokenizer};
+    pub use crate::analysis::{Analyzer, StopWords, Tokenizer};
     pub use crate::core::{
         DocValue, Document, Field, IndexReader, IndexWriter, FLAG_NOT_STORED, FLAG_TOKENIZED,
     };
-    pub use crate::search::{Condition, IndexSearcher, Query};
+    pub use crate::search::{Condition, IndexSearcher, Query, SortField};
 }
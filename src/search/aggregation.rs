@@ -0,0 +1,659 @@
+use crate::core::{Column, DocValue, IndexReader};
+use crate::spi::Result;
+use std::collections::{BTreeMap, HashMap};
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+const DEFAULT_MAX_BUCKETS: usize = 10_000;
+
+/// Aggregation over the matched documents, computed from doc values of the field.
+/// Bucket aggregations accept nested sub-aggregations computed for each bucket.
+pub enum Aggregation {
+    Terms(TermsAggregation),
+    Histogram(HistogramAggregation),
+    DateHistogram(DateHistogramAggregation),
+    Min(String),
+    Max(String),
+    Sum(String),
+    Avg(String),
+    ValueCount(String),
+    Percentiles(String, Vec<f64>),
+}
+
+pub enum AggregationResult {
+    Value(Option<f64>),
+    Percentiles(Vec<(f64, f64)>),
+    Buckets(Vec<Bucket>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermsOrder {
+    CountDesc,
+    CountAsc,
+    KeyAsc,
+    KeyDesc,
+}
+
+/// Counts distinct values of a field across matched documents, text fields must not be tokenized.
+pub struct TermsAggregation {
+    field: String,
+    size: usize,
+    min_doc_count: u64,
+    order: TermsOrder,
+    subs: Vec<(String, Aggregation)>,
+}
+
+/// Buckets numeric values by fixed interval, the key of a bucket is its lower bound.
+pub struct HistogramAggregation {
+    field: String,
+    interval: f64,
+    offset: f64,
+    min_doc_count: u64,
+    max_buckets: usize,
+    subs: Vec<(String, Aggregation)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateInterval {
+    Fixed(u64),
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+/// Buckets long fields of epoch milliseconds (UTC) by calendar or fixed interval.
+pub struct DateHistogramAggregation {
+    field: String,
+    interval: DateInterval,
+    min_doc_count: u64,
+    max_buckets: usize,
+    subs: Vec<(String, Aggregation)>,
+}
+
+pub struct Bucket {
+    key: String,
+    number: Option<f64>,
+    doc_count: u64,
+    aggs: HashMap<String, AggregationResult>,
+}
+
+/// Columns of a field, cached by segment.
+struct FieldColumns<'a> {
+    reader: &'a IndexReader,
+    field: &'a str,
+    cache: HashMap<u32, Option<&'a Column>>,
+}
+
+impl Aggregation {
+    pub fn min<N: Into<String>>(field: N) -> Aggregation {
+        Aggregation::Min(field.into())
+    }
+
+    pub fn max<N: Into<String>>(field: N) -> Aggregation {
+        Aggregation::Max(field.into())
+    }
+
+    pub fn sum<N: Into<String>>(field: N) -> Aggregation {
+        Aggregation::Sum(field.into())
+    }
+
+    pub fn avg<N: Into<String>>(field: N) -> Aggregation {
+        Aggregation::Avg(field.into())
+    }
+
+    pub fn value_count<N: Into<String>>(field: N) -> Aggregation {
+        Aggregation::ValueCount(field.into())
+    }
+
+    /// Percents are in range [0, 100], values between ranks are interpolated linearly.
+    pub fn percentiles<N: Into<String>>(field: N, percents: &[f64]) -> Aggregation {
+        Aggregation::Percentiles(field.into(), percents.to_vec())
+    }
+
+    pub(crate) fn collect(&self, reader: &IndexReader, docs: &[u64]) -> Result<AggregationResult> {
+        let result = match self {
+            Aggregation::Terms(agg) => AggregationResult::Buckets(agg.collect(reader, docs)?),
+            Aggregation::Histogram(agg) => AggregationResult::Buckets(agg.collect(reader, docs)?),
+            Aggregation::DateHistogram(agg) => {
+                AggregationResult::Buckets(agg.collect(reader, docs)?)
+            }
+            Aggregation::Min(field) => {
+                let values = numbers(reader, field, docs);
+                AggregationResult::Value(values.into_iter().fold(None, |acc, v| match acc {
+                    Some(n) if n <= v => Some(n),
+                    _ => Some(v),
+                }))
+            }
+            Aggregation::Max(field) => {
+                let values = numbers(reader, field, docs);
+                AggregationResult::Value(values.into_iter().fold(None, |acc, v| match acc {
+                    Some(n) if n >= v => Some(n),
+                    _ => Some(v),
+                }))
+            }
+            Aggregation::Sum(field) => {
+                AggregationResult::Value(Some(numbers(reader, field, docs).iter().sum()))
+            }
+            Aggregation::Avg(field) => {
+                let values = numbers(reader, field, docs);
+                if values.is_empty() {
+                    AggregationResult::Value(None)
+                } else {
+                    let sum: f64 = values.iter().sum();
+                    AggregationResult::Value(Some(sum / values.len() as f64))
+                }
+            }
+            Aggregation::ValueCount(field) => {
+                AggregationResult::Value(Some(numbers(reader, field, docs).len() as f64))
+            }
+            Aggregation::Percentiles(field, percents) => {
+                let mut values = numbers(reader, field, docs);
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                let mut results = vec![];
+                if !values.is_empty() {
+                    for p in percents {
+                        let rank = p.clamp(0.0, 100.0) / 100.0 * (values.len() - 1) as f64;
+                        let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+                        let v = values[lo] + (values[hi] - values[lo]) * (rank - lo as f64);
+                        results.push((*p, v));
+                    }
+                }
+                AggregationResult::Percentiles(results)
+            }
+        };
+        Ok(result)
+    }
+}
+
+impl From<TermsAggregation> for Aggregation {
+    fn from(agg: TermsAggregation) -> Aggregation {
+        Aggregation::Terms(agg)
+    }
+}
+
+impl From<HistogramAggregation> for Aggregation {
+    fn from(agg: HistogramAggregation) -> Aggregation {
+        Aggregation::Histogram(agg)
+    }
+}
+
+impl From<DateHistogramAggregation> for Aggregation {
+    fn from(agg: DateHistogramAggregation) -> Aggregation {
+        Aggregation::DateHistogram(agg)
+    }
+}
+
+impl AggregationResult {
+    /// Value of a metric aggregation.
+    pub fn value(&self) -> Option<f64> {
+        match self {
+            AggregationResult::Value(v) => *v,
+            _ => None,
+        }
+    }
+
+    /// Pairs of percent and value of a percentiles aggregation.
+    pub fn percentiles(&self) -> &[(f64, f64)] {
+        match self {
+            AggregationResult::Percentiles(v) => v,
+            _ => &[],
+        }
+    }
+
+    pub fn buckets(&self) -> &[Bucket] {
+        match self {
+            AggregationResult::Buckets(v) => v,
+            _ => &[],
+        }
+    }
+}
+
+impl Bucket {
+    fn new(key: String, number: Option<f64>, docs: &[u64]) -> Bucket {
+        Bucket {
+            key,
+            number,
+            doc_count: docs.len() as u64,
+            aggs: HashMap::new(),
+        }
+    }
+
+    fn nest(
+        mut self,
+        reader: &IndexReader,
+        subs: &[(String, Aggregation)],
+        docs: &[u64],
+    ) -> Result<Self> {
+        for (name, agg) in subs {
+            self.aggs.insert(name.clone(), agg.collect(reader, docs)?);
+        }
+        Ok(self)
+    }
+
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    /// Numeric key of numeric terms and histogram buckets, epoch millis for dates.
+    pub fn get_number(&self) -> Option<f64> {
+        self.number
+    }
+
+    pub fn get_doc_count(&self) -> u64 {
+        self.doc_count
+    }
+
+    pub fn get_aggregation(&self, name: &str) -> Option<&AggregationResult> {
+        self.aggs.get(name)
+    }
+}
+
+impl TermsAggregation {
+    pub fn new<N>(field: N) -> TermsAggregation
+    where
+        N: Into<String>,
+    {
+        TermsAggregation {
+            field: field.into(),
+            size: 10,
+            min_doc_count: 1,
+            order: TermsOrder::CountDesc,
+            subs: vec![],
+        }
+    }
+
+    /// Max number of buckets returned, 10 by default.
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Buckets with less documents are omitted, 0 includes values not matched at all.
+    pub fn min_doc_count(mut self, n: u64) -> Self {
+        self.min_doc_count = n;
+        self
+    }
+
+    pub fn order(mut self, order: TermsOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn sub<N, A>(mut self, name: N, agg: A) -> Self
+    where
+        N: Into<String>,
+        A: Into<Aggregation>,
+    {
+        self.subs.push((name.into(), agg.into()));
+        self
+    }
+
+    pub(crate) fn collect(&self, reader: &IndexReader, docs: &[u64]) -> Result<Vec<Bucket>> {
+        // numeric values are kept to order keys, longs are exact.
+        let mut groups: HashMap<String, (Option<DocValue>, Vec<u64>)> = HashMap::new();
+        for (segment, ids) in by_segment(docs) {
+            match reader.column(segment, &self.field) {
+                Some(Column::Sorted(column)) => {
+                    let mut ords: Vec<Vec<u64>> = vec![vec![]; column.values()];
+                    for id in ids {
+                        if let Some(ord) = column.ord(id as u32) {
+                            ords[ord as usize].push(id);
+                        }
+                    }
+                    for (ord, mut found) in ords.into_iter().enumerate() {
+                        if !found.is_empty() {
+                            let key = column.lookup(ord as u32).to_string();
+                            groups.entry(key).or_default().1.append(&mut found);
+                        }
+                    }
+                }
+                Some(column) => {
+                    for id in ids {
+                        match column.value(id as u32) {
+                            Some(DocValue::Double(v)) if v.is_nan() => (),
+                            Some(v) => {
+                                let group = groups.entry(v.to_string()).or_default();
+                                group.0 = Some(v);
+                                group.1.push(id);
+                            }
+                            None => (),
+                        }
+                    }
+                }
+                None => (),
+            }
+        }
+        if self.min_doc_count == 0 {
+            for segment in reader.segment_ids() {
+                if let Some(Column::Sorted(column)) = reader.column(segment, &self.field) {
+                    for ord in 0..column.values() {
+                        groups
+                            .entry(column.lookup(ord as u32).to_string())
+                            .or_default();
+                    }
+                }
+            }
+        }
+        let mut groups: Vec<(String, Option<DocValue>, Vec<u64>)> = groups
+            .into_iter()
+            .filter(|(_, (_, ids))| ids.len() as u64 >= self.min_doc_count)
+            .map(|(key, (number, ids))| (key, number, ids))
+            .collect();
+        let by_key = |a: &(String, Option<DocValue>, Vec<u64>),
+                      b: &(String, Option<DocValue>, Vec<u64>)| match (
+            &a.1, &b.1,
+        ) {
+            (Some(x), Some(y)) => x.cmp(y),
+            _ => a.0.cmp(&b.0),
+        };
+        groups.sort_by(|a, b| match self.order {
+            TermsOrder::CountDesc => b.2.len().cmp(&a.2.len()).then(by_key(a, b)),
+            TermsOrder::CountAsc => a.2.len().cmp(&b.2.len()).then(by_key(a, b)),
+            TermsOrder::KeyAsc => by_key(a, b),
+            TermsOrder::KeyDesc => by_key(b, a),
+        });
+        groups
+            .into_iter()
+            .take(self.size)
+            .map(|(key, value, ids)| {
+                let number = value.and_then(|v| v.as_f64());
+                Bucket::new(key, number, &ids).nest(reader, &self.subs, &ids)
+            })
+            .collect()
+    }
+}
+
+impl HistogramAggregation {
+    pub fn new<N>(field: N, interval: f64) -> HistogramAggregation
+    where
+        N: Into<String>,
+    {
+        HistogramAggregation {
+            field: field.into(),
+            interval,
+            offset: 0.0,
+            min_doc_count: 0,
+            max_buckets: DEFAULT_MAX_BUCKETS,
+            subs: vec![],
+        }
+    }
+
+    /// Shift bucket boundaries, eg: interval 10 with offset 5 makes buckets [5, 15), [15, 25).
+    pub fn offset(mut self, offset: f64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Buckets with less documents are omitted, 0 fills the empty buckets between values.
+    pub fn min_doc_count(mut self, n: u64) -> Self {
+        self.min_doc_count = n;
+        self
+    }
+
+    /// Fail when more buckets would be built, including the empty ones, 10000 by default.
+    pub fn max_buckets(mut self, n: usize) -> Self {
+        self.max_buckets = n;
+        self
+    }
+
+    pub fn sub<N, A>(mut self, name: N, agg: A) -> Self
+    where
+        N: Into<String>,
+        A: Into<Aggregation>,
+    {
+        self.subs.push((name.into(), agg.into()));
+        self
+    }
+
+    pub(crate) fn collect(&self, reader: &IndexReader, docs: &[u64]) -> Result<Vec<Bucket>> {
+        if self.interval <= 0.0 {
+            return Ok(vec![]);
+        }
+        let mut columns = FieldColumns::new(reader, &self.field);
+        let mut groups: BTreeMap<i64, Vec<u64>> = BTreeMap::new();
+        for id in docs {
+            match columns.get(*id).and_then(|c| c.number(*id as u32)) {
+                Some(v) if !v.is_nan() => {
+                    let n = ((v - self.offset) / self.interval).floor() as i64;
+                    groups.entry(n).or_default().push(*id);
+                }
+                _ => (),
+            }
+        }
+        let groups = fill(groups, self.min_doc_count, self.max_buckets, |n| n + 1)?;
+        groups
+            .into_iter()
+            .map(|(n, ids)| {
+                let key = n as f64 * self.interval + self.offset;
+                Bucket::new(key.to_string(), Some(key), &ids).nest(reader, &self.subs, &ids)
+            })
+            .collect()
+    }
+}
+
+impl DateInterval {
+    /// Start of the interval containing the timestamp.
+    fn floor(self, millis: i64) -> i64 {
+        let days = millis.div_euclid(MILLIS_PER_DAY);
+        match self {
+            DateInterval::Fixed(n) => {
+                let n = std::cmp::max(n, 1) as i64;
+                millis.div_euclid(n) * n
+            }
+            DateInterval::Minute => millis.div_euclid(60_000) * 60_000,
+            DateInterval::Hour => millis.div_euclid(3_600_000) * 3_600_000,
+            DateInterval::Day => days * MILLIS_PER_DAY,
+            // weeks start on monday, 1970-01-01 is a thursday.
+            DateInterval::Week => (days - (days + 3).rem_euclid(7)) * MILLIS_PER_DAY,
+            DateInterval::Month | DateInterval::Quarter | DateInterval::Year => {
+                let (y, m, _) = civil_from_days(days);
+                let m = match self {
+                    DateInterval::Month => m,
+                    DateInterval::Quarter => (m - 1) / 3 * 3 + 1,
+                    _ => 1,
+                };
+                days_from_civil(y, m, 1) * MILLIS_PER_DAY
+            }
+        }
+    }
+
+    /// Start of the next interval, start must be floored already.
+    fn next(self, start: i64) -> i64 {
+        let months = match self {
+            DateInterval::Fixed(n) => return start + std::cmp::max(n, 1) as i64,
+            DateInterval::Minute => return start + 60_000,
+            DateInterval::Hour => return start + 3_600_000,
+            DateInterval::Day => return start + MILLIS_PER_DAY,
+            DateInterval::Week => return start + 7 * MILLIS_PER_DAY,
+            DateInterval::Month => 1,
+            DateInterval::Quarter => 3,
+            DateInterval::Year => 12,
+        };
+        let (y, m, _) = civil_from_days(start.div_euclid(MILLIS_PER_DAY));
+        let total = y * 12 + (m - 1) + months;
+        days_from_civil(total.div_euclid(12), total.rem_euclid(12) + 1, 1) * MILLIS_PER_DAY
+    }
+}
+
+impl DateHistogramAggregation {
+    pub fn new<N>(field: N, interval: DateInterval) -> DateHistogramAggregation
+    where
+        N: Into<String>,
+    {
+        DateHistogramAggregation {
+            field: field.into(),
+            interval,
+            min_doc_count: 0,
+            max_buckets: DEFAULT_MAX_BUCKETS,
+            subs: vec![],
+        }
+    }
+
+    /// Buckets with less documents are omitted, 0 fills the empty buckets between values.
+    pub fn min_doc_count(mut self, n: u64) -> Self {
+        self.min_doc_count = n;
+        self
+    }
+
+    /// Fail when more buckets would be built, including the empty ones, 10000 by default.
+    pub fn max_buckets(mut self, n: usize) -> Self {
+        self.max_buckets = n;
+        self
+    }
+
+    pub fn sub<N, A>(mut self, name: N, agg: A) -> Self
+    where
+        N: Into<String>,
+        A: Into<Aggregation>,
+    {
+        self.subs.push((name.into(), agg.into()));
+        self
+    }
+
+    pub(crate) fn collect(&self, reader: &IndexReader, docs: &[u64]) -> Result<Vec<Bucket>> {
+        let mut columns = FieldColumns::new(reader, &self.field);
+        let mut groups: BTreeMap<i64, Vec<u64>> = BTreeMap::new();
+        for id in docs {
+            if let Some(v) = columns.get(*id).and_then(|c| c.long(*id as u32)) {
+                groups.entry(self.interval.floor(v)).or_default().push(*id);
+            }
+        }
+        let interval = self.interval;
+        let groups = fill(groups, self.min_doc_count, self.max_buckets, |n| {
+            interval.next(n)
+        })?;
+        groups
+            .into_iter()
+            .map(|(start, ids)| {
+                Bucket::new(format_millis(start), Some(start as f64), &ids)
+                    .nest(reader, &self.subs, &ids)
+            })
+            .collect()
+    }
+}
+
+impl<'a> FieldColumns<'a> {
+    fn new(reader: &'a IndexReader, field: &'a str) -> FieldColumns<'a> {
+        FieldColumns {
+            reader,
+            field,
+            cache: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, id: u64) -> Option<&'a Column> {
+        let (reader, field) = (self.reader, self.field);
+        *self
+            .cache
+            .entry((id >> 32) as u32)
+            .or_insert_with(|| reader.column((id >> 32) as u32, field))
+    }
+}
+
+#[inline]
+fn by_segment(docs: &[u64]) -> HashMap<u32, Vec<u64>> {
+    let mut results: HashMap<u32, Vec<u64>> = HashMap::new();
+    for id in docs {
+        results.entry((id >> 32) as u32).or_default().push(*id);
+    }
+    results
+}
+
+#[inline]
+fn numbers(reader: &IndexReader, field: &str, docs: &[u64]) -> Vec<f64> {
+    let mut columns = FieldColumns::new(reader, field);
+    docs.iter()
+        .filter_map(|id| columns.get(*id).and_then(|c| c.number(*id as u32)))
+        .filter(|v| !v.is_nan())
+        .collect()
+}
+
+/// Drop buckets under min_doc_count, or fill the gaps between buckets when it is 0. Fails when
+/// there are more than max_buckets.
+fn fill<F>(
+    groups: BTreeMap<i64, Vec<u64>>,
+    min_doc_count: u64,
+    max_buckets: usize,
+    next: F,
+) -> Result<BTreeMap<i64, Vec<u64>>>
+where
+    F: Fn(i64) -> i64,
+{
+    let too_many = || format!("too many buckets: more than max_buckets {}", max_buckets);
+    if min_doc_count > 0 {
+        let results: BTreeMap<i64, Vec<u64>> = groups
+            .into_iter()
+            .filter(|(_, ids)| ids.len() as u64 >= min_doc_count)
+            .collect();
+        if results.len() > max_buckets {
+            return Err(too_many().into());
+        }
+        return Ok(results);
+    }
+    let mut results = BTreeMap::new();
+    let (first, last) = match (groups.keys().next(), groups.keys().next_back()) {
+        (Some(a), Some(b)) => (*a, *b),
+        _ => return Ok(results),
+    };
+    let mut cursor = first;
+    while cursor < last {
+        if results.len() >= max_buckets {
+            return Err(too_many().into());
+        }
+        results.insert(cursor, vec![]);
+        cursor = next(cursor);
+    }
+    results.extend(groups);
+    if results.len() > max_buckets {
+        return Err(too_many().into());
+    }
+    Ok(results)
+}
+
+// http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (
+        if m <= 2 {
+            yoe + era * 400 + 1
+        } else {
+            yoe + era * 400
+        },
+        m,
+        d,
+    )
+}
+
+fn format_millis(millis: i64) -> String {
+    let (y, m, d) = civil_from_days(millis.div_euclid(MILLIS_PER_DAY));
+    let secs = millis.rem_euclid(MILLIS_PER_DAY) / 1000;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y,
+        m,
+        d,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
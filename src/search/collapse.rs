@@ -0,0 +1,93 @@
+use crate::core::{Column, IndexReader};
+use std::collections::HashMap;
+
+/// Collapse search results by a keyword field, keeping the top document of each distinct value.
+/// Documents without value are collapsed into one group.
+#[derive(Debug, Clone)]
+pub struct Collapse {
+    field: String,
+    inner_hits: usize,
+}
+
+/// Documents sharing the same value of the collapse field, in result order.
+#[derive(Debug, Clone)]
+pub struct Group {
+    key: Option<String>,
+    total: usize,
+    hits: Vec<u64>,
+}
+
+impl Collapse {
+    pub fn new<N>(field: N) -> Collapse
+    where
+        N: Into<String>,
+    {
+        Collapse {
+            field: field.into(),
+            inner_hits: 1,
+        }
+    }
+
+    /// Number of top documents kept for each group, 1 by default.
+    pub fn inner_hits(mut self, n: usize) -> Self {
+        self.inner_hits = std::cmp::max(n, 1);
+        self
+    }
+
+    pub fn get_field(&self) -> &str {
+        &self.field
+    }
+
+    /// Group sorted documents, the first document of a group is the best one.
+    pub(crate) fn collect(&self, reader: &IndexReader, docs: &[u64]) -> Vec<Group> {
+        let mut columns: HashMap<u32, Option<&Column>> = HashMap::new();
+        let mut index: HashMap<Option<String>, usize> = HashMap::new();
+        let mut groups: Vec<Group> = vec![];
+        for id in docs {
+            let segment = (id >> 32) as u32;
+            let column = *columns
+                .entry(segment)
+                .or_insert_with(|| reader.column(segment, &self.field));
+            let key = column.and_then(|c| match c {
+                Column::Sorted(c) => c.ord(*id as u32).map(|n| c.lookup(n).to_string()),
+                c => c.number(*id as u32).map(|n| n.to_string()),
+            });
+            let i = *index.entry(key.clone()).or_insert_with(|| {
+                groups.push(Group {
+                    key,
+                    total: 0,
+                    hits: vec![],
+                });
+                groups.len() - 1
+            });
+            let group = &mut groups[i];
+            group.total += 1;
+            if group.hits.len() < self.inner_hits {
+                group.hits.push(*id);
+            }
+        }
+        groups
+    }
+}
+
+impl Group {
+    /// Value of the collapse field, None for documents without value.
+    pub fn get_key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Id of the best document in group.
+    pub fn get_id(&self) -> u64 {
+        self.hits[0]
+    }
+
+    /// Number of matched documents in group.
+    pub fn get_total(&self) -> usize {
+        self.total
+    }
+
+    /// Ids of the top documents in group, the best one first.
+    pub fn get_inner_hits(&self) -> &[u64] {
+        &self.hits
+    }
+}
@@ -0,0 +1,37 @@
+use crate::spi::Result;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::Arc;
+
+/// Thread pool evaluating the segments of a search concurrently, cheap to clone and share
+/// between searchers.
+#[derive(Clone)]
+pub struct Executor {
+    pool: Arc<ThreadPool>,
+}
+
+impl Executor {
+    pub fn new(threads: usize) -> Result<Executor> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("jets-search-{}", i))
+            .build()?;
+        Ok(Executor {
+            pool: Arc::new(pool),
+        })
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Map items on the pool, results are in the order of items.
+    pub(crate) fn map<T, R, F>(&self, items: Vec<T>, f: F) -> Vec<R>
+    where
+        T: Send,
+        R: Send,
+        F: Fn(T) -> R + Send + Sync,
+    {
+        self.pool.install(|| items.into_par_iter().map(f).collect())
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+
+/// Explanation of the score of a document, a tree mirroring the query conditions.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    matched: bool,
+    value: f32,
+    description: String,
+    details: Vec<Explanation>,
+}
+
+impl Explanation {
+    pub(crate) fn matched<D>(value: f32, description: D, details: Vec<Explanation>) -> Explanation
+    where
+        D: Into<String>,
+    {
+        Explanation {
+            matched: true,
+            value,
+            description: description.into(),
+            details,
+        }
+    }
+
+    pub(crate) fn no_match<D>(description: D, details: Vec<Explanation>) -> Explanation
+    where
+        D: Into<String>,
+    {
+        Explanation {
+            matched: false,
+            value: 0.0,
+            description: description.into(),
+            details,
+        }
+    }
+
+    pub fn is_match(&self) -> bool {
+        self.matched
+    }
+
+    pub fn get_value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn get_details(&self) -> &[Explanation] {
+        &self.details
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{}{} = {}",
+            "  ".repeat(depth),
+            self.value,
+            self.description
+        )?;
+        for it in self.details.iter() {
+            it.write(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// Inverse document frequency of BM25, postings keep no term frequency so it is the term score.
+#[inline]
+pub(crate) fn idf(doc_freq: u64, num_docs: u64) -> f32 {
+    let n = doc_freq as f64;
+    let total = std::cmp::max(num_docs, doc_freq) as f64;
+    (1.0 + (total - n + 0.5) / (n + 0.5)).ln() as f32
+}
+
+/// Explanation of the idf of a term.
+pub(crate) fn explain_idf(doc_freq: u64, num_docs: u64) -> Explanation {
+    Explanation::matched(
+        idf(doc_freq, num_docs),
+        "idf, computed as log(1 + (N - n + 0.5) / (n + 0.5)) from:",
+        vec![
+            Explanation::matched(
+                doc_freq as f32,
+                "n, number of documents containing term",
+                vec![],
+            ),
+            Explanation::matched(
+                std::cmp::max(num_docs, doc_freq) as f32,
+                "N, total number of documents",
+                vec![],
+            ),
+        ],
+    )
+}
+
+/// Explanation of a clause multiplied by its boost, detail itself unless boosted and matched.
+pub(crate) fn boosted(detail: Explanation, boost: f32) -> Explanation {
+    if boost == 1.0 || !detail.is_match() {
+        return detail;
+    }
+    let value = detail.get_value() * boost;
+    let boost = Explanation::matched(boost, "boost", vec![]);
+    Explanation::matched(value, "boost, product of:", vec![detail, boost])
+}
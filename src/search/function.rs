@@ -0,0 +1,449 @@
+use super::explain::{boosted, Explanation};
+use super::query::Condition;
+use crate::core::{Column, IndexReader};
+use crate::spi::Result;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Combines the score of a query with functions of the document, eg: popularity or freshness.
+pub struct FunctionScore {
+    query: Condition,
+    functions: Vec<(ScoreFunction, f32)>,
+    score_mode: ScoreMode,
+    boost_mode: BoostMode,
+    boost: f32,
+}
+
+/// How the values of functions are combined together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreMode {
+    Multiply,
+    Sum,
+    Avg,
+    Max,
+    Min,
+    First,
+}
+
+/// How the combined function value is combined with the query score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoostMode {
+    Multiply,
+    Replace,
+    Sum,
+    Avg,
+    Max,
+    Min,
+}
+
+pub enum ScoreFunction {
+    FieldValueFactor(FieldValueFactor),
+    Decay(Decay),
+    /// Uniform random score in [0, 1), stable for the same seed and document.
+    Random(u64),
+}
+
+/// Score from a numeric field: modifier(factor * value).
+pub struct FieldValueFactor {
+    field: String,
+    factor: f64,
+    modifier: Modifier,
+    missing: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    None,
+    Log,
+    Log1p,
+    Log2p,
+    Ln,
+    Ln1p,
+    Ln2p,
+    Square,
+    Sqrt,
+    Reciprocal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecayKind {
+    Gauss,
+    Linear,
+    Exp,
+}
+
+/// Score decaying with the distance to origin, it is `decay` at `offset + scale` away.
+/// Documents without value score 1.
+pub struct Decay {
+    kind: DecayKind,
+    target: DecayTarget,
+    origin: (f64, f64),
+    scale: f64,
+    offset: f64,
+    decay: f64,
+}
+
+enum DecayTarget {
+    Number(String),
+    Geo(String, String),
+}
+
+impl FunctionScore {
+    pub fn new(query: Condition) -> FunctionScore {
+        FunctionScore {
+            query,
+            functions: vec![],
+            score_mode: ScoreMode::Multiply,
+            boost_mode: BoostMode::Multiply,
+            boost: 1.0,
+        }
+    }
+
+    pub fn function(self, function: ScoreFunction) -> Self {
+        self.weighted(function, 1.0)
+    }
+
+    /// Add a function whose value is multiplied by weight.
+    pub fn weighted(mut self, function: ScoreFunction, weight: f32) -> Self {
+        self.functions.push((function, weight));
+        self
+    }
+
+    /// Multiply by default.
+    pub fn score_mode(mut self, mode: ScoreMode) -> Self {
+        self.score_mode = mode;
+        self
+    }
+
+    /// Multiply by default.
+    pub fn boost_mode(mut self, mode: BoostMode) -> Self {
+        self.boost_mode = mode;
+        self
+    }
+
+    /// Multiply the final score, 1 by default.
+    pub fn boost(mut self, boost: f32) -> Self {
+        self.boost = boost;
+        self
+    }
+
+    pub fn query(&self) -> &Condition {
+        &self.query
+    }
+
+    pub(crate) fn apply(&self, reader: &IndexReader, hits: Vec<(u64, f32)>) -> Vec<(u64, f32)> {
+        let mut cache = HashMap::new();
+        hits.into_iter()
+            .map(|(id, score)| {
+                let values: Vec<f32> = self
+                    .functions
+                    .iter()
+                    .map(|(f, weight)| f.compute(reader, &mut cache, id) * weight)
+                    .collect();
+                (id, self.combine(score, &values) * self.boost)
+            })
+            .collect()
+    }
+
+    pub(crate) fn explain(&self, reader: &IndexReader, id: u64, query: Explanation) -> Explanation {
+        if !query.is_match() {
+            return Explanation::no_match(
+                "function score, no match: query not matched",
+                vec![query],
+            );
+        }
+        let mut cache = HashMap::new();
+        let mut values = vec![];
+        let mut details = vec![];
+        for (f, weight) in self.functions.iter() {
+            let value = f.compute(reader, &mut cache, id) * weight;
+            values.push(value);
+            details.push(Explanation::matched(
+                value,
+                format!("{}, weight={}", f.describe(), weight),
+                vec![],
+            ));
+        }
+        let value = self.combine(query.get_value(), &values);
+        let functions = Explanation::matched(
+            combine_functions(self.score_mode, &values),
+            format!("functions combined by {:?}:", self.score_mode),
+            details,
+        );
+        let combined = Explanation::matched(
+            value,
+            format!(
+                "function score, {:?} of query score and functions:",
+                self.boost_mode
+            ),
+            vec![query, functions],
+        );
+        boosted(combined, self.boost)
+    }
+
+    fn combine(&self, score: f32, values: &[f32]) -> f32 {
+        if values.is_empty() {
+            return score;
+        }
+        let f = combine_functions(self.score_mode, values);
+        match self.boost_mode {
+            BoostMode::Multiply => score * f,
+            BoostMode::Replace => f,
+            BoostMode::Sum => score + f,
+            BoostMode::Avg => (score + f) / 2.0,
+            BoostMode::Max => score.max(f),
+            BoostMode::Min => score.min(f),
+        }
+    }
+}
+
+impl From<FunctionScore> for Condition {
+    fn from(f: FunctionScore) -> Condition {
+        Condition::FunctionScore(Box::new(f))
+    }
+}
+
+impl ScoreFunction {
+    pub fn field_value_factor(f: FieldValueFactor) -> ScoreFunction {
+        ScoreFunction::FieldValueFactor(f)
+    }
+
+    pub fn decay(d: Decay) -> ScoreFunction {
+        ScoreFunction::Decay(d)
+    }
+
+    pub fn random(seed: u64) -> ScoreFunction {
+        ScoreFunction::Random(seed)
+    }
+
+    fn compute<'a>(
+        &self,
+        reader: &'a IndexReader,
+        cache: &mut HashMap<(String, u32), Option<&'a Column>>,
+        id: u64,
+    ) -> f32 {
+        let mut number = |field: &str| -> Option<f64> {
+            let segment = (id >> 32) as u32;
+            cache
+                .entry((field.to_string(), segment))
+                .or_insert_with(|| reader.column(segment, field))
+                .and_then(|c| c.number(id as u32))
+        };
+        let v = match self {
+            ScoreFunction::FieldValueFactor(f) => match number(&f.field).or(f.missing) {
+                Some(v) => f.modifier.apply(f.factor * v),
+                None => 1.0,
+            },
+            ScoreFunction::Decay(d) => {
+                let distance = match &d.target {
+                    DecayTarget::Number(field) => number(field).map(|v| (v - d.origin.0).abs()),
+                    DecayTarget::Geo(lat, lon) => match (number(lat), number(lon)) {
+                        (Some(lat), Some(lon)) => Some(haversine(d.origin, (lat, lon))),
+                        _ => None,
+                    },
+                };
+                match distance {
+                    Some(distance) => d.compute(distance),
+                    None => 1.0,
+                }
+            }
+            ScoreFunction::Random(seed) => {
+                (splitmix64(seed ^ id) >> 11) as f64 / (1u64 << 53) as f64
+            }
+        };
+        v as f32
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            ScoreFunction::FieldValueFactor(f) => format!(
+                "field value factor, {:?}({} * doc['{}'])",
+                f.modifier, f.factor, f.field
+            ),
+            ScoreFunction::Decay(d) => match &d.target {
+                DecayTarget::Number(field) => format!(
+                    "{:?} decay of doc['{}'], origin={}, scale={}, offset={}, decay={}",
+                    d.kind, field, d.origin.0, d.scale, d.offset, d.decay
+                ),
+                DecayTarget::Geo(lat, lon) => format!(
+                    "{:?} decay of doc['{}','{}'], origin={:?}, scale={}km, offset={}km, decay={}",
+                    d.kind, lat, lon, d.origin, d.scale, d.offset, d.decay
+                ),
+            },
+            ScoreFunction::Random(seed) => format!("random score, seed={}", seed),
+        }
+    }
+}
+
+impl FieldValueFactor {
+    pub fn new<N>(field: N) -> FieldValueFactor
+    where
+        N: Into<String>,
+    {
+        FieldValueFactor {
+            field: field.into(),
+            factor: 1.0,
+            modifier: Modifier::None,
+            missing: None,
+        }
+    }
+
+    pub fn factor(mut self, factor: f64) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    pub fn modifier(mut self, modifier: Modifier) -> Self {
+        self.modifier = modifier;
+        self
+    }
+
+    /// Value of documents without the field, they score 1 if not set.
+    pub fn missing(mut self, value: f64) -> Self {
+        self.missing = Some(value);
+        self
+    }
+}
+
+impl Modifier {
+    /// Logarithms of values not greater than 0 are taken of the smallest positive double,
+    /// square roots of negative values are 0, so scores stay finite and ordered. Other
+    /// results which are not finite, eg: the reciprocal of 0, are 0.
+    fn apply(self, v: f64) -> f64 {
+        let clamp = |x: f64| x.max(f64::MIN_POSITIVE);
+        let v = match self {
+            Modifier::None => v,
+            Modifier::Log => clamp(v).log10(),
+            Modifier::Log1p => clamp(v + 1.0).log10(),
+            Modifier::Log2p => clamp(v + 2.0).log10(),
+            Modifier::Ln => clamp(v).ln(),
+            Modifier::Ln1p if v > -1.0 => v.ln_1p(),
+            Modifier::Ln1p => clamp(0.0).ln(),
+            Modifier::Ln2p => clamp(v + 2.0).ln(),
+            Modifier::Square => v * v,
+            Modifier::Sqrt => v.max(0.0).sqrt(),
+            Modifier::Reciprocal => 1.0 / v,
+        };
+        if v.is_finite() {
+            v
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Decay {
+    /// Decay over a numeric field. Errors unless scale is positive and finite.
+    pub fn number<N>(kind: DecayKind, field: N, origin: f64, scale: f64) -> Result<Decay>
+    where
+        N: Into<String>,
+    {
+        Ok(Decay {
+            kind,
+            target: DecayTarget::Number(field.into()),
+            origin: (origin, 0.0),
+            scale: check_scale(scale)?,
+            offset: 0.0,
+            decay: 0.5,
+        })
+    }
+
+    /// Decay over a long field of epoch millis, offset is set in millis too. Errors if
+    /// scale is zero.
+    pub fn date<N>(kind: DecayKind, field: N, origin: i64, scale: Duration) -> Result<Decay>
+    where
+        N: Into<String>,
+    {
+        Decay::number(kind, field, origin as f64, scale.as_millis() as f64)
+    }
+
+    /// Decay over the distance in kilometers to origin (lat, lon), the point of a document
+    /// is read from a latitude field and a longitude field. Errors unless scale is positive
+    /// and finite.
+    pub fn geo<N>(
+        kind: DecayKind,
+        lat: N,
+        lon: N,
+        origin: (f64, f64),
+        scale_km: f64,
+    ) -> Result<Decay>
+    where
+        N: Into<String>,
+    {
+        Ok(Decay {
+            kind,
+            target: DecayTarget::Geo(lat.into(), lon.into()),
+            origin,
+            scale: check_scale(scale_km)?,
+            offset: 0.0,
+            decay: 0.5,
+        })
+    }
+
+    /// Documents within offset to origin score 1.
+    pub fn offset(mut self, offset: f64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Score at scale away from origin plus offset, 0.5 by default. Errors unless it is
+    /// in (0, 1).
+    pub fn decay(mut self, decay: f64) -> Result<Self> {
+        if !(decay > 0.0 && decay < 1.0) {
+            return Err(format!("invalid decay: {}, it must be in (0, 1)", decay).into());
+        }
+        self.decay = decay;
+        Ok(self)
+    }
+
+    fn compute(&self, distance: f64) -> f64 {
+        let d = (distance - self.offset).max(0.0);
+        match self.kind {
+            DecayKind::Gauss => {
+                let sigma2 = -self.scale * self.scale / (2.0 * self.decay.ln());
+                (-d * d / (2.0 * sigma2)).exp()
+            }
+            DecayKind::Exp => (self.decay.ln() / self.scale * d).exp(),
+            DecayKind::Linear => {
+                let s = self.scale / (1.0 - self.decay);
+                ((s - d) / s).max(0.0)
+            }
+        }
+    }
+}
+
+fn check_scale(scale: f64) -> Result<f64> {
+    if !(scale > 0.0 && scale.is_finite()) {
+        return Err(format!("invalid scale: {}, it must be positive", scale).into());
+    }
+    Ok(scale)
+}
+
+fn combine_functions(mode: ScoreMode, values: &[f32]) -> f32 {
+    match mode {
+        ScoreMode::Multiply => values.iter().product(),
+        ScoreMode::Sum => values.iter().sum(),
+        ScoreMode::Avg => values.iter().sum::<f32>() / values.len() as f32,
+        ScoreMode::Max => values.iter().cloned().fold(f32::MIN, f32::max),
+        ScoreMode::Min => values.iter().cloned().fold(f32::MAX, f32::min),
+        ScoreMode::First => values[0],
+    }
+}
+
+fn haversine(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.1 - a.1).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+#[inline]
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
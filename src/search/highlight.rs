@@ -0,0 +1,234 @@
+use super::query::{Condition, Query};
+use crate::analysis::{Analyzer, Tokenizer};
+use crate::core::{DocValue, Document, FLAG_TOKENIZED};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Highlighter re-analyzes stored text of documents and wraps the tokens matched by query.
+/// Use the same tokenizer and field analyzers as the `IndexWriter`.
+/// Offsets are not stored in the index: postings keep doc ids only, and re-analyzing with the
+/// analyzers of the writer gives the same offsets without growing every segment.
+pub struct Highlighter<A>
+where
+    A: Tokenizer,
+{
+    tokenizer: A,
+    analyzers: HashMap<String, Analyzer>,
+    default_analyzer: Analyzer,
+    pre_tag: String,
+    post_tag: String,
+    fragment_size: usize,
+    max_fragments: usize,
+}
+
+pub struct HighlighterBuilder<A>
+where
+    A: Tokenizer,
+{
+    inner: Highlighter<A>,
+}
+
+struct Fragment {
+    start: usize,
+    end: usize,
+    matches: Vec<(usize, usize)>,
+}
+
+impl<A> Highlighter<A>
+where
+    A: Tokenizer,
+{
+    pub fn builder(tokenizer: A) -> HighlighterBuilder<A> {
+        HighlighterBuilder {
+            inner: Highlighter {
+                tokenizer,
+                analyzers: HashMap::new(),
+                default_analyzer: Analyzer::default(),
+                pre_tag: "<em>".to_string(),
+                post_tag: "</em>".to_string(),
+                fragment_size: 100,
+                max_fragments: 3,
+            },
+        }
+    }
+
+    /// Highlight every tokenized text field of document, fields without any match are omitted.
+    pub fn highlight(&self, query: &Query, doc: &Document) -> HashMap<String, Vec<String>> {
+        let mut results = HashMap::new();
+        for field in doc.get_fields() {
+            if field.get_flag() & FLAG_TOKENIZED == 0 {
+                continue;
+            }
+            if let DocValue::Text(text) = field.get_value() {
+                let fragments = self.highlight_text(query, field.get_name(), text);
+                if !fragments.is_empty() {
+                    results.insert(field.get_name().clone(), fragments);
+                }
+            }
+        }
+        results
+    }
+
+    /// Highlight text of a field, best fragments first.
+    pub fn highlight_text(&self, query: &Query, field: &str, text: &str) -> Vec<String> {
+        let mut terms = HashSet::new();
+        collect_terms(query.root(), field, &mut terms);
+        if terms.is_empty() {
+            return vec![];
+        }
+        let analyzer = self.analyzers.get(field).unwrap_or(&self.default_analyzer);
+        let mut matches: Vec<(usize, usize)> = analyzer
+            .analyze(&self.tokenizer, text)
+            .into_iter()
+            .filter(|it| it.get_end() > it.get_start() && terms.contains(it.get_text()))
+            .map(|it| (it.get_start(), it.get_end()))
+            .collect();
+        matches.sort();
+        // merge overlapped matches, eg: "北京" and "北京大学" in search mode.
+        let mut merged: Vec<(usize, usize)> = vec![];
+        for (start, end) in matches {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => {
+                    if end > last.1 {
+                        last.1 = end;
+                    }
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+        let mut fragments = self.fragments(text, merged);
+        fragments.sort_by_key(|it| Reverse(it.matches.len()));
+        fragments
+            .into_iter()
+            .take(self.max_fragments)
+            .map(|it| self.render(text, &it))
+            .collect()
+    }
+
+    fn fragments(&self, text: &str, matches: Vec<(usize, usize)>) -> Vec<Fragment> {
+        if self.fragment_size == 0 {
+            if matches.is_empty() {
+                return vec![];
+            }
+            return vec![Fragment {
+                start: 0,
+                end: text.len(),
+                matches,
+            }];
+        }
+        // byte offset of each char, plus the end of text.
+        let mut chars: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        chars.push(text.len());
+        let char_at = |offset: usize| match chars.binary_search(&offset) {
+            Ok(n) => n,
+            Err(n) => n,
+        };
+        let total = chars.len() - 1;
+        let mut results: Vec<Fragment> = vec![];
+        for (start, end) in matches {
+            if let Some(last) = results.last_mut() {
+                // the previous fragment grows to cover a match crossing its end.
+                if start < last.end {
+                    last.end = std::cmp::max(last.end, end);
+                    last.matches.push((start, end));
+                    continue;
+                }
+            }
+            let (cs, ce) = (char_at(start), char_at(end));
+            let size = std::cmp::max(self.fragment_size, ce - cs);
+            let mut fs = cs.saturating_sub((size - (ce - cs)) / 2);
+            let fe = std::cmp::min(fs + size, total);
+            if fe - fs < size {
+                fs = fe.saturating_sub(size);
+            }
+            if let Some(last) = results.last() {
+                if chars[fs] < last.end {
+                    fs = char_at(last.end);
+                }
+            }
+            results.push(Fragment {
+                start: chars[fs],
+                end: chars[fe],
+                matches: vec![(start, end)],
+            });
+        }
+        results
+    }
+
+    fn render(&self, text: &str, fragment: &Fragment) -> String {
+        let mut s = String::new();
+        let mut cursor = fragment.start;
+        for (start, end) in fragment.matches.iter() {
+            if *start < cursor || *end > fragment.end {
+                continue;
+            }
+            s.push_str(&text[cursor..*start]);
+            s.push_str(&self.pre_tag);
+            s.push_str(&text[*start..*end]);
+            s.push_str(&self.post_tag);
+            cursor = *end;
+        }
+        s.push_str(&text[cursor..fragment.end]);
+        s
+    }
+}
+
+impl<A> HighlighterBuilder<A>
+where
+    A: Tokenizer,
+{
+    pub fn analyzer<N>(mut self, field: N, analyzer: Analyzer) -> Self
+    where
+        N: Into<String>,
+    {
+        self.inner.analyzers.insert(field.into(), analyzer);
+        self
+    }
+
+    pub fn pre_tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.inner.pre_tag = tag.into();
+        self
+    }
+
+    pub fn post_tag<T>(mut self, tag: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.inner.post_tag = tag.into();
+        self
+    }
+
+    /// Size of each fragment in characters, 0 means the whole text.
+    pub fn fragment_size(mut self, size: usize) -> Self {
+        self.inner.fragment_size = size;
+        self
+    }
+
+    pub fn max_fragments(mut self, n: usize) -> Self {
+        self.inner.max_fragments = n;
+        self
+    }
+
+    pub fn build(self) -> Highlighter<A> {
+        self.inner
+    }
+}
+
+fn collect_terms<'a>(cond: &'a Condition, field: &str, terms: &mut HashSet<&'a str>) {
+    match cond {
+        Condition::Term(k, v, _) => {
+            if k == field {
+                terms.insert(v);
+            }
+        }
+        Condition::Group(_, conds, _) => {
+            for it in conds {
+                collect_terms(it, field, terms);
+            }
+        }
+        Condition::FunctionScore(f) => collect_terms(f.query(), field, terms),
+    }
+}
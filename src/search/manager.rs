@@ -0,0 +1,61 @@
+use super::searcher::IndexSearcher;
+use crate::analysis::Tokenizer;
+use crate::core::{IndexReader, IndexWriter};
+use crate::spi::Result;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Hands out the current searcher to many threads and swaps in searchers of reopened readers.
+/// A searcher acquired before a refresh stays valid until the last reference is dropped.
+pub struct SearcherManager {
+    current: RwLock<Arc<IndexSearcher>>,
+    // one refresh at a time, so a reader is not reopened twice.
+    refreshing: Mutex<()>,
+}
+
+impl From<IndexSearcher> for SearcherManager {
+    fn from(searcher: IndexSearcher) -> SearcherManager {
+        SearcherManager {
+            current: RwLock::new(Arc::new(searcher)),
+            refreshing: Mutex::new(()),
+        }
+    }
+}
+
+impl SearcherManager {
+    pub fn open(path: &str) -> Result<SearcherManager> {
+        Ok(IndexSearcher::from(IndexReader::open(path)?).into())
+    }
+
+    pub fn acquire(&self) -> Arc<IndexSearcher> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Reopen the reader of current searcher, true if the latest commit has new segments and
+    /// a new searcher is swapped in. See `IndexReader::reopen`.
+    pub fn maybe_refresh(&self) -> Result<bool> {
+        let _refreshing = self.refreshing.lock().unwrap();
+        let current = self.acquire();
+        match current.reader().reopen()? {
+            Some(reader) => {
+                self.swap(current.with_reader(reader));
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Swap in a searcher of the near-real-time reader of writer, see `IndexWriter::reader`.
+    pub fn refresh_from<A>(&self, writer: &IndexWriter<A>) -> Result<()>
+    where
+        A: 'static + Tokenizer,
+    {
+        let _refreshing = self.refreshing.lock().unwrap();
+        let reader = writer.reader()?;
+        self.swap(self.acquire().with_reader(reader));
+        Ok(())
+    }
+
+    fn swap(&self, searcher: IndexSearcher) {
+        *self.current.write().unwrap() = Arc::new(searcher);
+    }
+}
@@ -1,5 +1,29 @@
+mod aggregation;
+mod collapse;
+mod executor;
+mod explain;
+mod function;
+mod highlight;
+mod manager;
+mod profile;
 mod query;
 mod searcher;
+mod sort;
 
+pub use aggregation::{
+    Aggregation, AggregationResult, Bucket, DateHistogramAggregation, DateInterval,
+    HistogramAggregation, TermsAggregation, TermsOrder,
+};
+pub use collapse::{Collapse, Group};
+pub use executor::Executor;
+pub use explain::Explanation;
+pub use function::{
+    BoostMode, Decay, DecayKind, FieldValueFactor, FunctionScore, Modifier, ScoreFunction,
+    ScoreMode,
+};
+pub use highlight::{Highlighter, HighlighterBuilder};
+pub use manager::SearcherManager;
+pub use profile::{Profile, ProfileNode, SegmentPostings};
 pub use query::{Condition, Operator, Query};
 pub use searcher::{IndexSearcher, TopDocs};
+pub use sort::{Missing, SortField, SortOrder};
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Profile of a search, enabled by `Query::profile`.
+#[derive(Debug, Clone)]
+pub struct Profile {
+    query: ProfileNode,
+    sort: Duration,
+    collapse: Duration,
+    fetch: Option<Duration>,
+}
+
+/// Timing and statistics of a condition, children mirror the query conditions.
+#[derive(Debug, Clone)]
+pub struct ProfileNode {
+    description: String,
+    elapsed: Duration,
+    hits: usize,
+    fst_lookups: usize,
+    postings: Vec<SegmentPostings>,
+    children: Vec<ProfileNode>,
+}
+
+/// Size of the posting list of a term fetched from a segment.
+#[derive(Debug, Clone)]
+pub struct SegmentPostings {
+    term: String,
+    segment: u32,
+    size: usize,
+}
+
+impl Profile {
+    pub(crate) fn new(query: ProfileNode, sort: Duration, collapse: Duration) -> Profile {
+        Profile {
+            query,
+            sort,
+            collapse,
+            fetch: None,
+        }
+    }
+
+    pub(crate) fn set_fetch(&mut self, elapsed: Duration) {
+        self.fetch = Some(self.fetch.unwrap_or_default() + elapsed);
+    }
+
+    pub fn get_query(&self) -> &ProfileNode {
+        &self.query
+    }
+
+    pub fn get_sort(&self) -> Duration {
+        self.sort
+    }
+
+    pub fn get_collapse(&self) -> Duration {
+        self.collapse
+    }
+
+    /// Time of loading documents from store, None before `TopDocs::documents` is called.
+    pub fn get_fetch(&self) -> Option<Duration> {
+        self.fetch
+    }
+}
+
+impl ProfileNode {
+    pub(crate) fn new<D>(description: D) -> ProfileNode
+    where
+        D: Into<String>,
+    {
+        ProfileNode {
+            description: description.into(),
+            elapsed: Duration::default(),
+            hits: 0,
+            fst_lookups: 0,
+            postings: vec![],
+            children: vec![],
+        }
+    }
+
+    pub(crate) fn done(&mut self, elapsed: Duration, hits: usize) {
+        self.elapsed = elapsed;
+        self.hits = hits;
+    }
+
+    pub(crate) fn lookup(&mut self, term: &str, segment: u32, size: Option<usize>) {
+        self.fst_lookups += 1;
+        if let Some(size) = size {
+            self.postings.push(SegmentPostings {
+                term: term.to_string(),
+                segment,
+                size,
+            });
+        }
+    }
+
+    pub(crate) fn push(&mut self, child: ProfileNode) {
+        self.children.push(child);
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    /// Time spent in the condition, including its children.
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Number of documents matched by the condition.
+    pub fn get_hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of FST lookups of the condition itself, one per segment having the field.
+    pub fn get_fst_lookups(&self) -> usize {
+        self.fst_lookups
+    }
+
+    /// Posting lists found by the condition itself.
+    pub fn get_postings(&self) -> &[SegmentPostings] {
+        &self.postings
+    }
+
+    pub fn get_children(&self) -> &[ProfileNode] {
+        &self.children
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        writeln!(
+            f,
+            "{}{}: elapsed={}us, hits={}, fst_lookups={}",
+            indent,
+            self.description,
+            self.elapsed.as_micros(),
+            self.hits,
+            self.fst_lookups
+        )?;
+        for it in self.postings.iter() {
+            writeln!(
+                f,
+                "{}  - postings: term={}, segment={}, size={}",
+                indent, it.term, it.segment, it.size
+            )?;
+        }
+        for it in self.children.iter() {
+            it.write(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl SegmentPostings {
+    pub fn get_term(&self) -> &str {
+        &self.term
+    }
+
+    pub fn get_segment(&self) -> u32 {
+        self.segment
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.query.write(f, 0)?;
+        writeln!(f, "sort: elapsed={}us", self.sort.as_micros())?;
+        writeln!(f, "collapse: elapsed={}us", self.collapse.as_micros())?;
+        match self.fetch {
+            Some(n) => writeln!(f, "fetch: elapsed={}us", n.as_micros()),
+            None => writeln!(f, "fetch: -"),
+        }
+    }
+}
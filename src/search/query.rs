@@ -1,20 +1,61 @@
+use super::collapse::Collapse;
+use super::function::FunctionScore;
+use super::sort::SortField;
+
 pub enum Operator {
     AND,
     OR,
 }
 
+/// Term and group conditions end with a boost multiplying their score, 1 by default.
 pub enum Condition {
-    Group(Operator, Vec<Condition>),
-    Term(String, String),
+    Group(Operator, Vec<Condition>, f32),
+    Term(String, String, f32),
+    FunctionScore(Box<FunctionScore>),
+}
+
+impl Condition {
+    pub fn term<K, V>(field: K, value: V) -> Condition
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Condition::Term(field.into(), value.into(), 1.0)
+    }
+
+    pub fn group(op: Operator, conds: Vec<Condition>) -> Condition {
+        Condition::Group(op, conds, 1.0)
+    }
+
+    /// Multiply the score of this clause by boost.
+    pub fn boost(self, boost: f32) -> Condition {
+        match self {
+            Condition::Group(op, conds, _) => Condition::Group(op, conds, boost),
+            Condition::Term(k, v, _) => Condition::Term(k, v, boost),
+            Condition::FunctionScore(f) => Condition::FunctionScore(Box::new(f.boost(boost))),
+        }
+    }
 }
 
 pub struct Query {
     head: Condition,
+    sorts: Vec<SortField>,
+    collapse: Option<Collapse>,
+    offset: usize,
+    limit: Option<usize>,
+    profile: bool,
 }
 
 impl From<Condition> for Query {
     fn from(c: Condition) -> Query {
-        Query { head: c }
+        Query {
+            head: c,
+            sorts: vec![],
+            collapse: None,
+            offset: 0,
+            limit: None,
+            profile: false,
+        }
     }
 }
 
@@ -22,4 +63,53 @@ impl Query {
     pub fn root(&self) -> &Condition {
         &self.head
     }
+
+    /// Append a sort key, results are in doc id order without any.
+    pub fn sort(mut self, sort: SortField) -> Self {
+        self.sorts.push(sort);
+        self
+    }
+
+    pub fn sorts(&self) -> &[SortField] {
+        &self.sorts
+    }
+
+    pub fn collapse(mut self, collapse: Collapse) -> Self {
+        self.collapse = Some(collapse);
+        self
+    }
+
+    pub fn get_collapse(&self) -> Option<&Collapse> {
+        self.collapse.as_ref()
+    }
+
+    /// Skip the first n results, or groups when collapsed.
+    pub fn offset(mut self, n: usize) -> Self {
+        self.offset = n;
+        self
+    }
+
+    /// Max number of results, or groups when collapsed.
+    pub fn limit(mut self, n: usize) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Record timings and statistics of the search, see `TopDocs::profile`.
+    pub fn profile(mut self, enabled: bool) -> Self {
+        self.profile = enabled;
+        self
+    }
+
+    pub fn is_profile(&self) -> bool {
+        self.profile
+    }
+
+    pub(crate) fn paginate<T>(&self, items: Vec<T>) -> Vec<T> {
+        let it = items.into_iter().skip(self.offset);
+        match self.limit {
+            Some(n) => it.take(n).collect(),
+            None => it.collect(),
+        }
+    }
 }
@@ -1,126 +1,678 @@
+use super::aggregation::{Aggregation, AggregationResult, Bucket, TermsAggregation};
+use super::collapse::Group;
+use super::executor::Executor;
+use super::explain::{boosted, explain_idf, idf, Explanation};
+use super::profile::{Profile, ProfileNode};
 use super::query::{Condition, Operator, Query};
+use super::sort::{compare_keys, sort_keyed, SortField, SortValue};
+use crate::analysis::{Analyzer, Token, Tokenizer, WhitespaceTokenizer};
 use crate::core::DocValue;
 use crate::core::Document;
 use crate::core::IndexReader;
+use crate::spi::Result;
 use crate::utils::Stack;
-use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
+const MAX_EXPANDED_PATHS: usize = 64;
+
+/// Searcher of a point-in-time reader, it is `Send + Sync` so one can serve many threads.
 pub struct IndexSearcher {
     inner: IndexReader,
+    // shared by searchers of reopened readers.
+    analyzers: HashMap<String, Arc<Analyzer>>,
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+    executor: Option<Executor>,
 }
 
 pub struct TopDocs<'a> {
     reader: &'a IndexReader,
-    docs: Option<Vec<u64>>,
+    hits: Vec<u64>,
+    page: Vec<u64>,
+    groups: Option<(usize, Vec<Group>)>,
+    scores: HashMap<u64, f32>,
+    profile: Option<Mutex<Profile>>,
 }
 
 impl<'a> TopDocs<'a> {
+    /// Documents of the requested page, the best document of each group when collapsed.
     pub fn documents(&'a self) -> Option<Vec<Document>> {
-        match &self.docs {
-            Some(ids) => {
-                let mut vv = vec![];
-                for id in ids {
-                    if let Some(d) = self.reader.document(*id) {
-                        vv.push(d);
-                    }
-                }
-                Some(vv)
+        if self.page.is_empty() {
+            return None;
+        }
+        let begin = Instant::now();
+        let mut vv = vec![];
+        for id in self.page.iter() {
+            if let Some(d) = self.reader.document(*id) {
+                vv.push(d);
             }
-            None => None,
         }
+        if let Some(profile) = &self.profile {
+            profile.lock().unwrap().set_fetch(begin.elapsed());
+        }
+        Some(vv)
+    }
+
+    /// Profile of the search if enabled by `Query::profile`.
+    pub fn profile(&self) -> Option<Profile> {
+        self.profile.as_ref().map(|it| it.lock().unwrap().clone())
+    }
+
+    pub fn document(&self, id: u64) -> Option<Document> {
+        self.reader.document(id)
+    }
+
+    /// Score of a matched document.
+    pub fn score(&self, id: u64) -> Option<f32> {
+        self.scores.get(&id).copied()
+    }
+
+    /// Number of matched documents, regardless of pagination.
+    pub fn total_hits(&self) -> usize {
+        self.hits.len()
+    }
+
+    /// Number of groups regardless of pagination, None if not collapsed.
+    pub fn total_groups(&self) -> Option<usize> {
+        self.groups.as_ref().map(|(n, _)| *n)
+    }
+
+    /// Groups of the requested page, empty if not collapsed.
+    pub fn groups(&self) -> &[Group] {
+        match &self.groups {
+            Some((_, groups)) => groups,
+            None => &[],
+        }
+    }
+
+    /// Count values of a not tokenized field across the matched documents.
+    pub fn terms(&self, agg: &TermsAggregation) -> Result<Vec<Bucket>> {
+        agg.collect(self.reader, &self.hits)
+    }
+
+    /// Fails when a bucket aggregation builds more buckets than its max_buckets.
+    pub fn aggregate(&self, agg: &Aggregation) -> Result<AggregationResult> {
+        agg.collect(self.reader, &self.hits)
     }
 }
 
 impl From<IndexReader> for IndexSearcher {
     fn from(reader: IndexReader) -> IndexSearcher {
-        IndexSearcher { inner: reader }
+        IndexSearcher {
+            inner: reader,
+            analyzers: HashMap::new(),
+            tokenizer: None,
+            executor: None,
+        }
     }
 }
 
+type Hits = Vec<(u64, f32)>;
+
+/// Hits in result order, with their sort keys.
+type Ranked<'a> = Vec<(u64, f32, Vec<Option<SortValue<'a>>>)>;
+
+/// Postings of the query words in one segment, scored by frequencies in all segments.
+struct SegmentScope<'a> {
+    words: &'a HashMap<(String, String), usize>,
+    postings: Vec<Vec<u64>>,
+    freqs: &'a [u64],
+}
+
 enum RuntimeCond {
     Group,
-    Bingo(Vec<u64>),
+    Bingo(Hits),
 }
 
 impl IndexSearcher {
+    /// Register the analyzer applied to term values of a field at query time, eg: synonyms.
+    pub fn set_analyzer<N>(&mut self, field: N, analyzer: Analyzer)
+    where
+        N: Into<String>,
+    {
+        self.analyzers.insert(field.into(), Arc::new(analyzer));
+    }
+
+    /// Tokenizer of term values of the fields with an analyzer, usually the one of the writer.
+    /// Defaults to the tokenizer of the dictionary stored with the index, see
+    /// `IndexReader::get_tokenizer`. Without any, a value is split by whitespace.
+    pub fn set_tokenizer<T>(&mut self, tokenizer: T)
+    where
+        T: 'static + Tokenizer,
+    {
+        self.tokenizer = Some(Arc::new(tokenizer));
+    }
+
+    /// Evaluate segments of each search concurrently on executor, results are the same as a
+    /// sequential search. Profiled searches are sequential.
+    pub fn set_executor(&mut self, executor: Executor) {
+        self.executor = Some(executor);
+    }
+
+    /// Searcher of a newer reader, with the analyzers and tokenizer of this one.
+    pub(crate) fn with_reader(&self, reader: IndexReader) -> IndexSearcher {
+        IndexSearcher {
+            inner: reader,
+            analyzers: self.analyzers.clone(),
+            tokenizer: self.tokenizer.clone(),
+            executor: self.executor.clone(),
+        }
+    }
+
+    pub(crate) fn reader(&self) -> &IndexReader {
+        &self.inner
+    }
+
+    /// Results are ranked by score unless the query has sort keys.
     pub fn search<'a>(&'a self, query: &Query) -> TopDocs<'a> {
-        let mut stack: Stack<RuntimeCond> = Stack::new();
-        self.process(query.root(), &mut stack);
-        let docs = match stack.pop() {
-            None => None,
-            Some(r) => match r {
-                RuntimeCond::Bingo(result) => {
-                    if result.len() < 1 {
-                        None
-                    } else {
-                        Some(result)
-                    }
-                }
-                RuntimeCond::Group => unreachable!(),
-            },
+        let (node, scored, sort) = match &self.executor {
+            Some(executor) if !query.is_profile() => {
+                let scored = self.search_segments(executor, query);
+                (None, scored, Default::default())
+            }
+            _ => {
+                let mut stack: Stack<RuntimeCond> = Stack::new();
+                let node = self.process(query.root(), &mut stack, query.is_profile(), None);
+                let scored = match stack.pop() {
+                    None => vec![],
+                    Some(r) => match r {
+                        RuntimeCond::Bingo(result) => result,
+                        RuntimeCond::Group => unreachable!(),
+                    },
+                };
+                let begin = Instant::now();
+                let scored = self
+                    .rank(scored, query.sorts())
+                    .into_iter()
+                    .map(|(id, score, _)| (id, score))
+                    .collect();
+                (node, scored, begin.elapsed())
+            }
+        };
+        let hits: Vec<u64> = scored.iter().map(|(id, _)| *id).collect();
+        let begin = Instant::now();
+        let (page, groups) = match query.get_collapse() {
+            Some(collapse) => {
+                let groups = collapse.collect(&self.inner, &hits);
+                let total = groups.len();
+                let groups = query.paginate(groups);
+                let page = groups.iter().map(|it| it.get_id()).collect();
+                (page, Some((total, groups)))
+            }
+            None => (query.paginate(hits.clone()), None),
         };
+        let collapse = begin.elapsed();
         TopDocs {
             reader: &self.inner,
-            docs: docs,
+            hits,
+            page,
+            groups,
+            scores: scored.into_iter().collect(),
+            profile: node.map(|it| Mutex::new(Profile::new(it, sort, collapse))),
+        }
+    }
+
+    /// Lookup words of query in each segment, then evaluate and rank each segment with the
+    /// frequencies of words in all segments, ranked segments are merged at last.
+    fn search_segments(&self, executor: &Executor, query: &Query) -> Hits {
+        let mut words = HashMap::new();
+        self.collect_words(query.root(), &mut words);
+        let mut keys = vec![None; words.len()];
+        for (key, i) in words.iter() {
+            keys[*i] = Some(key);
+        }
+        let postings: Vec<Vec<Vec<u64>>> = executor.map(self.inner.segment_ids(), |segment| {
+            keys.iter()
+                .flatten()
+                .map(|(k, word)| {
+                    let found = self
+                        .inner
+                        .find_in(segment, k, DocValue::Text(word.to_string()));
+                    skip_failed(found, k, word).unwrap_or_default()
+                })
+                .collect()
+        });
+        let mut freqs = vec![0; words.len()];
+        for each in postings.iter() {
+            for (i, ids) in each.iter().enumerate() {
+                freqs[i] += ids.len() as u64;
+            }
         }
+        let ranked = executor.map(postings, |postings| {
+            let scope = SegmentScope {
+                words: &words,
+                postings,
+                freqs: &freqs,
+            };
+            let mut stack: Stack<RuntimeCond> = Stack::new();
+            self.process(query.root(), &mut stack, false, Some(&scope));
+            match stack.pop() {
+                Some(RuntimeCond::Bingo(scored)) => self.rank(scored, query.sorts()),
+                _ => vec![],
+            }
+        });
+        merge_ranked(ranked, query.sorts())
     }
 
-    fn process(&self, cond: &Condition, stack: &mut Stack<RuntimeCond>) {
+    fn collect_words(&self, cond: &Condition, words: &mut HashMap<(String, String), usize>) {
         match cond {
-            Condition::Term(k, v) => match self.inner.find(&k, DocValue::Text(v.clone())) {
-                Some(found) => stack.push(RuntimeCond::Bingo(found)),
-                None => stack.push(RuntimeCond::Bingo(vec![])),
-            },
-            Condition::Group(op, conds) => {
+            Condition::Term(k, v, _) => {
+                for word in self.expand(k, v).into_iter().flatten() {
+                    let n = words.len();
+                    words.entry((k.to_string(), word)).or_insert(n);
+                }
+            }
+            Condition::Group(_, conds, _) => {
+                for next in conds {
+                    self.collect_words(next, words);
+                }
+            }
+            Condition::FunctionScore(f) => self.collect_words(f.query(), words),
+        }
+    }
+
+    /// Sort hits by score or by sort keys, ties are kept in doc id order.
+    fn rank(&self, mut scored: Hits, sorts: &[SortField]) -> Ranked<'_> {
+        scored.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+        if sorts.is_empty() {
+            return scored
+                .into_iter()
+                .map(|(id, score)| (id, score, vec![]))
+                .collect();
+        }
+        let ids: Vec<u64> = scored.iter().map(|(id, _)| *id).collect();
+        let scores: HashMap<u64, f32> = scored.into_iter().collect();
+        sort_keyed(&self.inner, sorts, &ids)
+            .into_iter()
+            .map(|(id, keys)| (id, scores[&id], keys))
+            .collect()
+    }
+
+    /// Explain how a document matches the query and how its score is computed.
+    pub fn explain(&self, query: &Query, id: u64) -> Explanation {
+        self.explain_cond(query.root(), id)
+    }
+
+    fn process(
+        &self,
+        cond: &Condition,
+        stack: &mut Stack<RuntimeCond>,
+        profiling: bool,
+        scope: Option<&SegmentScope>,
+    ) -> Option<ProfileNode> {
+        let begin = Instant::now();
+        let mut node = if profiling {
+            Some(ProfileNode::new(describe(cond)))
+        } else {
+            None
+        };
+        let hits = match cond {
+            Condition::Term(k, v, boost) => {
+                let mut holder = vec![];
+                for path in self.expand(k, v) {
+                    let founds = path
+                        .iter()
+                        .map(|word| self.find(k, word, node.as_mut(), scope))
+                        .collect();
+                    holder.push(merge(&Operator::AND, founds));
+                }
+                boost_hits(dis_max(holder), *boost)
+            }
+            Condition::Group(op, conds, boost) => {
                 stack.push(RuntimeCond::Group);
                 for next in conds {
-                    self.process(next, stack);
+                    let child = self.process(next, stack, profiling, scope);
+                    if let (Some(node), Some(child)) = (node.as_mut(), child) {
+                        node.push(child);
+                    }
                 }
                 let mut holder = vec![];
                 while let Some(RuntimeCond::Bingo(bingo)) = stack.pop() {
                     holder.push(bingo);
                 }
-                // TODO: tuning: use FOR skip table or bitsets filter.
-                let merge: Vec<u64> = match op {
-                    Operator::AND => {
-                        let should = holder.len();
-                        let mut map: HashMap<u64, usize> = HashMap::new();
-                        for each in holder {
-                            for n in each {
-                                if map.contains_key(&n) {
-                                    let val = map.get(&n).unwrap() + 1;
-                                    map.insert(n, val);
-                                } else {
-                                    map.insert(n, 1);
-                                }
-                            }
-                        }
-                        let mut result = vec![];
-                        for (k, v) in map.into_iter() {
-                            if v >= should {
-                                result.push(k);
-                            }
-                        }
-                        result.sort();
-                        result
-                    }
-                    Operator::OR => {
-                        let mut sets = HashSet::new();
-                        for it in holder {
-                            sets.insert(it);
-                        }
-                        let mut v = vec![];
-                        for it in sets.into_iter() {
-                            for n in it {
-                                v.push(n);
-                            }
-                        }
-                        v.sort();
-                        v
-                    }
-                };
-                stack.push(RuntimeCond::Bingo(merge));
+                boost_hits(merge(op, holder), *boost)
+            }
+            Condition::FunctionScore(f) => {
+                let hits = self.process_inner(f.query(), stack, node.as_mut(), profiling, scope);
+                f.apply(&self.inner, hits)
+            }
+        };
+        if let Some(node) = node.as_mut() {
+            node.done(begin.elapsed(), hits.len());
+        }
+        stack.push(RuntimeCond::Bingo(hits));
+        node
+    }
+
+    /// Process the wrapped condition of cond and take its hits.
+    fn process_inner(
+        &self,
+        inner: &Condition,
+        stack: &mut Stack<RuntimeCond>,
+        node: Option<&mut ProfileNode>,
+        profiling: bool,
+        scope: Option<&SegmentScope>,
+    ) -> Hits {
+        let child = self.process(inner, stack, profiling, scope);
+        if let (Some(node), Some(child)) = (node, child) {
+            node.push(child);
+        }
+        match stack.pop() {
+            Some(RuntimeCond::Bingo(hits)) => hits,
+            _ => vec![],
+        }
+    }
+
+    fn explain_cond(&self, cond: &Condition, id: u64) -> Explanation {
+        match cond {
+            Condition::Term(k, v, boost) => boosted(self.explain_paths(k, v, id), *boost),
+            Condition::Group(op, conds, boost) => {
+                boosted(self.explain_group(op, conds, id), *boost)
+            }
+            Condition::FunctionScore(f) => {
+                f.explain(&self.inner, id, self.explain_cond(f.query(), id))
+            }
+        }
+    }
+
+    fn explain_paths(&self, k: &str, v: &str, id: u64) -> Explanation {
+        let expanded = self.expand(k, v);
+        if expanded.len() == 1 && expanded[0].len() == 1 {
+            return self.explain_term(k, &expanded[0][0], id);
+        }
+        let mut paths = vec![];
+        for path in expanded {
+            let words: Vec<Explanation> = path
+                .iter()
+                .map(|word| self.explain_term(k, word, id))
+                .collect();
+            let desc = format!("path {}:[{}], sum of:", k, path.join(" "));
+            if words.iter().all(|it| it.is_match()) {
+                let sum = words.iter().map(|it| it.get_value()).sum();
+                paths.push(Explanation::matched(sum, desc, words));
+            } else {
+                paths.push(Explanation::no_match(desc, words));
+            }
+        }
+        let desc = format!("term {}:{}, max of expanded paths:", k, v);
+        match paths
+            .iter()
+            .filter(|it| it.is_match())
+            .map(|it| it.get_value())
+            .fold(None, |acc: Option<f32>, v| {
+                Some(acc.map_or(v, |n| n.max(v)))
+            }) {
+            Some(max) => Explanation::matched(max, desc, paths),
+            None => Explanation::no_match(desc, paths),
+        }
+    }
+
+    fn explain_group(&self, op: &Operator, conds: &[Condition], id: u64) -> Explanation {
+        let details: Vec<Explanation> = conds.iter().map(|it| self.explain_cond(it, id)).collect();
+        let matched = details.iter().filter(|it| it.is_match()).count();
+        let sum = details
+            .iter()
+            .filter(|it| it.is_match())
+            .map(|it| it.get_value())
+            .sum();
+        match op {
+            Operator::AND if matched == details.len() && matched > 0 => {
+                Explanation::matched(sum, "AND, sum of:", details)
+            }
+            Operator::AND => {
+                Explanation::no_match("AND, no match: not all clauses matched", details)
+            }
+            Operator::OR if matched > 0 => {
+                Explanation::matched(sum, "OR, sum of matched clauses:", details)
+            }
+            Operator::OR => Explanation::no_match("OR, no match: no clause matched", details),
+        }
+    }
+
+    fn explain_term(&self, field: &str, word: &str, id: u64) -> Explanation {
+        let ids = self.lookup(field, word);
+        let idf = explain_idf(ids.len() as u64, self.inner.num_docs());
+        if ids.contains(&id) {
+            let desc = format!("term {}:{}, score of:", field, word);
+            Explanation::matched(idf.get_value(), desc, vec![idf])
+        } else {
+            let desc = format!(
+                "term {}:{}, no match: document does not contain term",
+                field, word
+            );
+            Explanation::no_match(desc, vec![idf])
+        }
+    }
+
+    /// Words of each path expanded by the query analyzer of field, or the value itself.
+    #[inline]
+    fn expand(&self, field: &str, value: &str) -> Vec<Vec<String>> {
+        match self.analyzers.get(field) {
+            Some(analyzer) => {
+                let tokenizer = self
+                    .tokenizer
+                    .as_deref()
+                    .or(self.inner.get_tokenizer())
+                    .unwrap_or(&WhitespaceTokenizer);
+                expand_paths(&analyzer.analyze_query(tokenizer, value))
+            }
+            None => vec![vec![value.to_string()]],
+        }
+    }
+
+    #[inline]
+    fn find(
+        &self,
+        field: &str,
+        word: &str,
+        node: Option<&mut ProfileNode>,
+        scope: Option<&SegmentScope>,
+    ) -> Hits {
+        if let Some(scope) = scope {
+            let i = scope.words[&(field.to_string(), word.to_string())];
+            let score = idf(scope.freqs[i], self.inner.num_docs());
+            return scope.postings[i].iter().map(|id| (*id, score)).collect();
+        }
+        let ids = match node {
+            Some(node) => {
+                let founds = self
+                    .inner
+                    .find_segments(field, DocValue::Text(word.to_string()));
+                let founds = skip_failed(founds, field, word);
+                let mut ids = vec![];
+                for (segment, found) in founds {
+                    node.lookup(word, segment, found.as_ref().map(|it| it.len()));
+                    ids.extend(found.unwrap_or_default());
+                }
+                ids
+            }
+            None => self.lookup(field, word),
+        };
+        let score = idf(ids.len() as u64, self.inner.num_docs());
+        ids.into_iter().map(|id| (id, score)).collect()
+    }
+
+    #[inline]
+    fn lookup(&self, field: &str, word: &str) -> Vec<u64> {
+        let found = self.inner.find(field, DocValue::Text(word.to_string()));
+        skip_failed(found, field, word)
+    }
+}
+
+/// Postings of a failed lookup are skipped, the error is logged with the segment it names.
+fn skip_failed<T: Default>(found: Result<T>, field: &str, word: &str) -> T {
+    found.unwrap_or_else(|e| {
+        warn!("skip lookup of {}:{}, {}", field, word, e);
+        T::default()
+    })
+}
+
+fn describe(cond: &Condition) -> String {
+    match cond {
+        Condition::Term(k, v, boost) => with_boost(format!("term {}:{}", k, v), *boost),
+        Condition::Group(Operator::AND, _, boost) => with_boost("AND".to_string(), *boost),
+        Condition::Group(Operator::OR, _, boost) => with_boost("OR".to_string(), *boost),
+        Condition::FunctionScore(_) => "function score".to_string(),
+    }
+}
+
+#[inline]
+fn with_boost(desc: String, boost: f32) -> String {
+    if boost == 1.0 {
+        desc
+    } else {
+        format!("{}, boost={}", desc, boost)
+    }
+}
+
+/// Multiply scores of hits by the boost of their clause.
+#[inline]
+fn boost_hits(hits: Hits, boost: f32) -> Hits {
+    if boost == 1.0 {
+        return hits;
+    }
+    hits.into_iter()
+        .map(|(id, score)| (id, score * boost))
+        .collect()
+}
+
+/// Merge ranked hits of segments in result order, by a heap of the head of each list.
+fn merge_ranked(lists: Vec<Ranked>, sorts: &[SortField]) -> Hits {
+    let total = lists.iter().map(|it| it.len()).sum();
+    let mut merged = Vec::with_capacity(total);
+    let mut iters: Vec<_> = lists.iter().map(|it| it.iter()).collect();
+    let mut heap = BinaryHeap::with_capacity(iters.len());
+    for (list, it) in iters.iter_mut().enumerate() {
+        if let Some(hit) = it.next() {
+            heap.push(Head { hit, list, sorts });
+        }
+    }
+    while let Some(Head { hit, list, .. }) = heap.pop() {
+        merged.push((hit.0, hit.1));
+        if let Some(hit) = iters[list].next() {
+            heap.push(Head { hit, list, sorts });
+        }
+    }
+    merged
+}
+
+/// Head of a ranked list, the greatest one in the heap is the next hit in result order.
+struct Head<'a, 'b> {
+    hit: &'b (u64, f32, Vec<Option<SortValue<'a>>>),
+    list: usize,
+    sorts: &'b [SortField],
+}
+
+impl Head<'_, '_> {
+    fn order(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.hit, other.hit);
+        let ord = if self.sorts.is_empty() {
+            b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal)
+        } else {
+            compare_keys(self.sorts, &a.2, &b.2)
+        };
+        ord.then(a.0.cmp(&b.0))
+    }
+}
+
+impl Ord for Head<'_, '_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.order(self)
+    }
+}
+
+impl PartialOrd for Head<'_, '_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head<'_, '_> {}
+
+/// Merge hits of clauses, scores of the matched clauses are summed.
+#[inline]
+fn merge(op: &Operator, holder: Vec<Hits>) -> Hits {
+    // TODO: tuning: use FOR skip table or bitsets filter.
+    let should = match op {
+        Operator::AND => holder.len(),
+        Operator::OR => 1,
+    };
+    let mut map: HashMap<u64, (usize, f32)> = HashMap::new();
+    for each in holder {
+        for (id, score) in each {
+            let found = map.entry(id).or_insert((0, 0.0));
+            found.0 += 1;
+            found.1 += score;
+        }
+    }
+    let mut result: Hits = map
+        .into_iter()
+        .filter(|(_, (n, _))| *n >= should)
+        .map(|(id, (_, score))| (id, score))
+        .collect();
+    result.sort_by_key(|(id, _)| *id);
+    result
+}
+
+/// Union hits of alternatives, the best score wins.
+#[inline]
+fn dis_max(holder: Vec<Hits>) -> Hits {
+    if holder.len() == 1 {
+        return holder.into_iter().next().unwrap();
+    }
+    let mut map: HashMap<u64, f32> = HashMap::new();
+    for each in holder {
+        for (id, score) in each {
+            let found = map.entry(id).or_insert(score);
+            if score > *found {
+                *found = score;
             }
         }
     }
+    let mut result: Hits = map.into_iter().collect();
+    result.sort_by_key(|(id, _)| *id);
+    result
+}
+
+/// Walk the token graph produced by query analysis, each path is a sequence of words
+/// from the first position to the last one.
+fn expand_paths(tokens: &[Token]) -> Vec<Vec<String>> {
+    let end = match tokens
+        .iter()
+        .map(|it| it.get_position() + it.get_span())
+        .max()
+    {
+        Some(n) => n,
+        None => return vec![],
+    };
+    let mut results = vec![];
+    let mut stack: Stack<(u32, Vec<String>)> = Stack::new();
+    stack.push((tokens[0].get_position(), vec![]));
+    while let Some((pos, path)) = stack.pop() {
+        if results.len() >= MAX_EXPANDED_PATHS {
+            break;
+        }
+        if pos >= end {
+            results.push(path);
+            continue;
+        }
+        for it in tokens.iter().filter(|it| it.get_position() == pos) {
+            let mut next = path.clone();
+            next.push(it.get_text().to_string());
+            stack.push((pos + it.get_span(), next));
+        }
+    }
+    results
 }
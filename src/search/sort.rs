@@ -0,0 +1,140 @@
+use crate::core::{Column, IndexReader};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Where documents without value are placed, whatever the order is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Missing {
+    First,
+    Last,
+}
+
+/// Sort key of search results, values are read from doc values of the field.
+#[derive(Debug, Clone)]
+pub struct SortField {
+    field: String,
+    order: SortOrder,
+    missing: Missing,
+}
+
+pub(crate) enum SortValue<'a> {
+    Number(f64),
+    Text(&'a str),
+}
+
+impl SortField {
+    pub fn asc<N>(field: N) -> SortField
+    where
+        N: Into<String>,
+    {
+        SortField {
+            field: field.into(),
+            order: SortOrder::Asc,
+            missing: Missing::Last,
+        }
+    }
+
+    pub fn desc<N>(field: N) -> SortField
+    where
+        N: Into<String>,
+    {
+        SortField {
+            field: field.into(),
+            order: SortOrder::Desc,
+            missing: Missing::Last,
+        }
+    }
+
+    /// Missing values are sorted last by default.
+    pub fn missing(mut self, missing: Missing) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    pub fn get_field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn get_order(&self) -> SortOrder {
+        self.order
+    }
+
+    fn compare(&self, a: &Option<SortValue>, b: &Option<SortValue>) -> Ordering {
+        let ord = match (a, b) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ord = match (x, y) {
+                    (SortValue::Number(x), SortValue::Number(y)) => {
+                        x.partial_cmp(y).unwrap_or(Ordering::Equal)
+                    }
+                    (SortValue::Text(x), SortValue::Text(y)) => x.cmp(y),
+                    (SortValue::Number(_), SortValue::Text(_)) => Ordering::Less,
+                    (SortValue::Text(_), SortValue::Number(_)) => Ordering::Greater,
+                };
+                return match self.order {
+                    SortOrder::Asc => ord,
+                    SortOrder::Desc => ord.reverse(),
+                };
+            }
+        };
+        match self.missing {
+            Missing::First => ord,
+            Missing::Last => ord.reverse(),
+        }
+    }
+}
+
+/// Documents with their sort keys, sorted by keys in turn, ties are kept in doc id order.
+pub(crate) fn sort_keyed<'a>(
+    reader: &'a IndexReader,
+    sorts: &[SortField],
+    docs: &[u64],
+) -> Vec<(u64, Vec<Option<SortValue<'a>>>)> {
+    let mut columns: Vec<HashMap<u32, Option<&Column>>> = vec![HashMap::new(); sorts.len()];
+    let mut keyed: Vec<(u64, Vec<Option<SortValue>>)> = docs
+        .iter()
+        .map(|id| {
+            let segment = (id >> 32) as u32;
+            let values = sorts
+                .iter()
+                .zip(columns.iter_mut())
+                .map(|(sort, cache)| {
+                    let column = *cache
+                        .entry(segment)
+                        .or_insert_with(|| reader.column(segment, &sort.field));
+                    match column? {
+                        Column::Sorted(c) => {
+                            c.ord(*id as u32).map(|n| SortValue::Text(c.lookup(n)))
+                        }
+                        c => c.number(*id as u32).map(SortValue::Number),
+                    }
+                })
+                .collect();
+            (*id, values)
+        })
+        .collect();
+    keyed.sort_by(|(a_id, a), (b_id, b)| compare_keys(sorts, a, b).then(a_id.cmp(b_id)));
+    keyed
+}
+
+pub(crate) fn compare_keys(
+    sorts: &[SortField],
+    a: &[Option<SortValue>],
+    b: &[Option<SortValue>],
+) -> Ordering {
+    for (i, sort) in sorts.iter().enumerate() {
+        let ord = sort.compare(&a[i], &b[i]);
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
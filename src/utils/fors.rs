@@ -1,4 +1,4 @@
-use super::misc::{get_v32, put_v32};
+use super::misc::{put_v32, read_v64};
 use crate::spi::Result;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use std::collections::LinkedList;
@@ -80,10 +80,18 @@ where
 
 impl FOR {
     pub fn decode(b: &mut Bytes) -> Result<FOR> {
+        let mut pos = 0;
+        let decoded = Self::decode_slice(&b[..], &mut pos)?;
+        b.advance(pos);
+        Ok(decoded)
+    }
+
+    /// Decode in place, eg: from a mmap'd file, pos is moved to the end of the last block.
+    pub fn decode_slice(data: &[u8], pos: &mut usize) -> Result<FOR> {
         let mut blocks: Vec<Block> = vec![];
         let mut more = true;
         while more {
-            Self::decode_block(b, &mut blocks, &mut more)?;
+            Self::decode_block(data, pos, &mut blocks, &mut more)?;
         }
         Ok(FOR {
             chunk: BLOCK_SIZE,
@@ -143,24 +151,40 @@ impl FOR {
     }
 
     #[inline]
-    fn decode_block(b: &mut Bytes, blocks: &mut Vec<Block>, has_more: &mut bool) -> Result<()> {
-        let header = b.get_u8();
+    fn decode_block(
+        data: &[u8],
+        pos: &mut usize,
+        blocks: &mut Vec<Block>,
+        has_more: &mut bool,
+    ) -> Result<()> {
+        let header = *data.get(*pos).ok_or("invalid FOR block: truncated")?;
+        *pos += 1;
         let num_bits = header & 0b00111111;
+        if num_bits > 32 {
+            return Err("invalid FOR block: too many bits".into());
+        }
         let amount = if header & FLAG_SIZED != 0 {
-            get_v32(b)? as usize
+            read_v64(data, pos).ok_or("invalid FOR block: truncated")? as usize
         } else {
             BLOCK_SIZE
         };
 
         let (n, cursor) = {
-            let bits = (num_bits as usize) * amount;
+            let bits = (num_bits as usize)
+                .checked_mul(amount)
+                .ok_or("invalid FOR block: too many values")?;
             let c = bits & 7;
             let n = if c == 0 { bits / 8 } else { 1 + bits / 8 };
             (n, c as u8)
         };
+        let bs = pos
+            .checked_add(n)
+            .and_then(|end| data.get(*pos..end))
+            .ok_or("invalid FOR block: truncated")?;
+        *pos += n;
         let block = Block {
             num_bits,
-            inner: Bits::new(cursor, b.split_to(n).to_vec()),
+            inner: Bits::new(cursor, bs.to_vec()),
         };
         blocks.push(block);
         *has_more = header & FLAG_MORE != 0;
@@ -234,7 +258,7 @@ impl Block {
     }
 
     fn len(&self) -> usize {
-        8 * self.inner.inner.len() / (self.num_bits as usize)
+        self.inner.len() / (self.num_bits as usize)
     }
 
     fn get(&self, i: usize) -> u32 {
@@ -51,6 +51,10 @@ where
         }
     }
 
+    pub fn get_lines(&self) -> &Vec<Line<T>> {
+        &self.lines
+    }
+
     pub fn traverse(&self, f: impl Fn(&Line<T>)) {
         for it in &self.lines {
             it.traverse(&f);
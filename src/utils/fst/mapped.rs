@@ -0,0 +1,174 @@
+use super::core::{Line, FST};
+use super::outputs::Outputs;
+use crate::io::Writer;
+use crate::spi::Result;
+use crate::utils::{put_v32, put_v64, read_v64};
+use bytes::{BufMut, BytesMut};
+
+const ARC_FINAL: u8 = 0x01 << 0;
+const ARC_HAS_VALUE: u8 = 0x01 << 1;
+const ARC_HAS_FINAL_VALUE: u8 = 0x01 << 2;
+const ARC_HAS_TARGET: u8 = 0x01 << 3;
+
+const CORRUPT_NODE: &str = "invalid mapped fst: truncated node";
+
+/// FST with u64 outputs queried in place, eg: from a mmap'd file.
+///
+/// Nodes are written children first, each one is a v32 arc count then its arcs sorted by label:
+/// `label, flag, [v64 value], [v64 final value], [v64 target]`. The root address is the last 8
+/// bytes, so a lookup only touches the nodes along the key.
+pub struct MappedFST<'a> {
+    data: &'a [u8],
+    root: usize,
+}
+
+struct Arc {
+    flag: u8,
+    value: u64,
+    final_value: u64,
+    target: usize,
+}
+
+impl<O> FST<u64, O>
+where
+    O: Outputs<Item = u64>,
+{
+    /// Save in the layout of `MappedFST`, returns the number of bytes written.
+    pub fn save_mapped(&self, writer: &mut impl Writer) -> Result<usize> {
+        let mut bf = BytesMut::new();
+        let root = write_node(self.get_lines(), &mut bf)?;
+        bf.put_u64(root as u64);
+        writer.put_slice(&bf[..]);
+        Ok(bf.len())
+    }
+}
+
+impl<'a> MappedFST<'a> {
+    pub fn open(data: &'a [u8]) -> Result<MappedFST<'a>> {
+        if data.len() < 8 {
+            return Err("invalid mapped fst: too short".into());
+        }
+        let mut root = [0u8; 8];
+        root.copy_from_slice(&data[data.len() - 8..]);
+        let root = u64::from_be_bytes(root) as usize;
+        if root >= data.len() - 8 {
+            return Err("invalid mapped fst: bad root address".into());
+        }
+        Ok(MappedFST {
+            data: &data[..data.len() - 8],
+            root,
+        })
+    }
+
+    /// Open the nodes of a FST opened before, eg: cached by a segment, see `get_root`.
+    pub fn with_root(data: &'a [u8], root: usize) -> MappedFST<'a> {
+        MappedFST { data, root }
+    }
+
+    /// Address of the root node, the nodes are `open`'s data without the last 8 bytes.
+    pub fn get_root(&self) -> usize {
+        self.root
+    }
+
+    /// Output of key, Err if a node on its path is truncated.
+    pub fn get<K>(&self, key: K) -> Result<Option<u64>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let mut node = self.root;
+        let mut sum = 0u64;
+        for (i, ch) in key.iter().enumerate() {
+            let arc = match self.find(node, *ch)? {
+                Some(arc) => arc,
+                None => return Ok(None),
+            };
+            sum = sum.wrapping_add(arc.value);
+            if i == key.len() - 1 {
+                if arc.flag & ARC_FINAL == 0 {
+                    return Ok(None);
+                }
+                return Ok(Some(sum.wrapping_add(arc.final_value)));
+            }
+            if arc.flag & ARC_HAS_TARGET == 0 {
+                return Ok(None);
+            }
+            node = arc.target;
+        }
+        Ok(None)
+    }
+
+    #[inline]
+    fn find(&self, node: usize, label: u8) -> Result<Option<Arc>> {
+        let mut pos = node;
+        for _ in 0..read_v64(self.data, &mut pos).ok_or(CORRUPT_NODE)? {
+            let current = *self.data.get(pos).ok_or(CORRUPT_NODE)?;
+            let flag = *self.data.get(pos + 1).ok_or(CORRUPT_NODE)?;
+            pos += 2;
+            let mut arc = Arc {
+                flag,
+                value: 0,
+                final_value: 0,
+                target: 0,
+            };
+            if flag & ARC_HAS_VALUE != 0 {
+                arc.value = read_v64(self.data, &mut pos).ok_or(CORRUPT_NODE)?;
+            }
+            if flag & ARC_HAS_FINAL_VALUE != 0 {
+                arc.final_value = read_v64(self.data, &mut pos).ok_or(CORRUPT_NODE)?;
+            }
+            if flag & ARC_HAS_TARGET != 0 {
+                arc.target = read_v64(self.data, &mut pos).ok_or(CORRUPT_NODE)? as usize;
+            }
+            if current == label {
+                return Ok(Some(arc));
+            }
+            if current > label {
+                return Ok(None);
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn write_node(lines: &[Line<u64>], bf: &mut BytesMut) -> Result<usize> {
+    let mut targets = vec![];
+    for it in lines {
+        if it.get_nexts().is_empty() {
+            targets.push(None);
+        } else {
+            targets.push(Some(write_node(it.get_nexts(), bf)?));
+        }
+    }
+    let address = bf.len();
+    put_v32(bf, lines.len() as u32)?;
+    for (it, target) in lines.iter().zip(targets) {
+        let value = it.get_value().unwrap_or(0);
+        let final_value = it.get_final_value().unwrap_or(0);
+        let mut flag = 0;
+        if it.is_final() {
+            flag |= ARC_FINAL;
+        }
+        if value != 0 {
+            flag |= ARC_HAS_VALUE;
+        }
+        if final_value != 0 {
+            flag |= ARC_HAS_FINAL_VALUE;
+        }
+        if target.is_some() {
+            flag |= ARC_HAS_TARGET;
+        }
+        bf.put_u8(it.get_label());
+        bf.put_u8(flag);
+        if value != 0 {
+            put_v64(bf, value)?;
+        }
+        if final_value != 0 {
+            put_v64(bf, final_value)?;
+        }
+        if let Some(target) = target {
+            put_v64(bf, target as u64)?;
+        }
+    }
+    Ok(address)
+}
@@ -1,7 +1,9 @@
 mod codec;
 mod core;
+mod mapped;
 mod outputs;
 
 pub use self::core::{Builder, Line, FST};
+pub use self::mapped::MappedFST;
 pub use codec::{Codec, CodecFOR, CodecV32, CodecVecU32, CodecVecU32OverFOR, CodecVecU64};
 pub use outputs::*;
@@ -190,3 +190,17 @@ pub fn get_v64(bf: &mut Bytes) -> Result<u64> {
     }
     Err("invalid v64 detected: too many bits.".into())
 }
+
+/// Read a v64 from data at pos in place, pos is moved after it. None if data is truncated.
+pub fn read_v64(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut result = 0u64;
+    for i in 0..10 {
+        let b = *data.get(*pos)?;
+        *pos += 1;
+        result |= ((b & 0x7F) as u64) << (i * 7);
+        if b & 0x80 == 0 {
+            return Some(result);
+        }
+    }
+    None
+}
@@ -3,10 +3,12 @@ use std::time::SystemTime;
 mod fors;
 pub mod fst;
 mod misc;
+mod packed;
 mod stack;
 
 pub use fors::FOR;
 pub use misc::*;
+pub use packed::PackedInts;
 pub use stack::Stack;
 
 pub fn unique_id() -> u64 {
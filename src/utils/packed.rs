@@ -0,0 +1,117 @@
+use super::misc::{get_v32, put_v32};
+use crate::spi::Result;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+const BLOCK_SIZE: usize = 128;
+
+/// Frame of reference over unordered u64 values with random access: each block of 128 values
+/// keeps its min, and every value is bit-packed as the delta to that min.
+#[derive(Debug)]
+pub struct PackedInts {
+    len: usize,
+    blocks: Vec<PackedBlock>,
+}
+
+#[derive(Debug)]
+struct PackedBlock {
+    min: u64,
+    num_bits: u8,
+    data: Bytes,
+}
+
+impl PackedInts {
+    pub fn new(inputs: &[u64]) -> PackedInts {
+        let mut blocks = vec![];
+        for chunk in inputs.chunks(BLOCK_SIZE) {
+            let min = *chunk.iter().min().unwrap();
+            let or = chunk.iter().fold(0u64, |acc, v| acc | (v - min));
+            let num_bits = (64 - or.leading_zeros()) as u8;
+            let mut data = vec![0u8; (chunk.len() * num_bits as usize).div_ceil(8)];
+            if num_bits > 0 {
+                for (i, v) in chunk.iter().enumerate() {
+                    let delta = v - min;
+                    let offset = i * num_bits as usize;
+                    for b in 0..num_bits as usize {
+                        if delta & (1 << b) != 0 {
+                            data[(offset + b) / 8] |= 1 << ((offset + b) % 8);
+                        }
+                    }
+                }
+            }
+            blocks.push(PackedBlock {
+                min,
+                num_bits,
+                data: Bytes::from(data),
+            });
+        }
+        PackedInts {
+            len: inputs.len(),
+            blocks,
+        }
+    }
+
+    pub fn decode(b: &mut Bytes) -> Result<PackedInts> {
+        let len = get_v32(b)? as usize;
+        let mut blocks = vec![];
+        let mut left = len;
+        while left > 0 {
+            let n = std::cmp::min(left, BLOCK_SIZE);
+            let min = b.get_u64();
+            let num_bits = b.get_u8();
+            if num_bits > 64 {
+                return Err("invalid packed ints: too many bits.".into());
+            }
+            let data = b.split_to((n * num_bits as usize).div_ceil(8));
+            blocks.push(PackedBlock {
+                min,
+                num_bits,
+                data,
+            });
+            left -= n;
+        }
+        Ok(PackedInts { len, blocks })
+    }
+
+    pub fn write_to(&self, bf: &mut BytesMut) -> Result<()> {
+        put_v32(bf, self.len as u32)?;
+        for block in self.blocks.iter() {
+            bf.put_u64(block.min);
+            bf.put_u8(block.num_bits);
+            bf.put_slice(&block.data);
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<u64> {
+        if i >= self.len {
+            return None;
+        }
+        let block = &self.blocks[i / BLOCK_SIZE];
+        let num_bits = block.num_bits as usize;
+        if num_bits == 0 {
+            return Some(block.min);
+        }
+        let offset = (i % BLOCK_SIZE) * num_bits;
+        // the value spans 9 bytes at most.
+        let mut window = 0u128;
+        let first = offset / 8;
+        let last = std::cmp::min((offset + num_bits).div_ceil(8), block.data.len());
+        for (k, b) in block.data[first..last].iter().enumerate() {
+            window |= (*b as u128) << (k * 8);
+        }
+        let mask = if num_bits == 64 {
+            u64::MAX
+        } else {
+            (1u64 << num_bits) - 1
+        };
+        Some(block.min + ((window >> (offset % 8)) as u64 & mask))
+    }
+}
@@ -0,0 +1,247 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+fn keys(buckets: &[Bucket]) -> Vec<(&str, u64)> {
+    buckets
+        .iter()
+        .map(|it| (it.get_key(), it.get_doc_count()))
+        .collect()
+}
+
+#[test]
+fn test_terms_aggregation() {
+    let path = "/tmp/jets/TEST_TERMS_AGGS";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let movies = vec![
+        ("北京的故事", "1994", "张艺谋"),
+        ("北京人在纽约", "1994", "冯小刚"),
+        ("上海滩", "1980", "张艺谋"),
+        ("北京遇上西雅图", "2013", "薛晓路"),
+        ("上海的早晨", "1994", "冯小刚"),
+    ];
+    for (i, (title, year, director)) in movies.into_iter().enumerate() {
+        let doc = Document::builder()
+            .put("title", DocValue::from(title), FLAG_TOKENIZED)
+            .put("year", DocValue::from(year), 0)
+            .put("director", DocValue::from(director), 0)
+            .build();
+        writer.push(doc).unwrap();
+        // spread documents over two segments.
+        if i == 2 {
+            writer.flush().unwrap();
+        }
+    }
+    writer.flush().unwrap();
+
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let q = Query::from(Condition::term("title".to_string(), "北京".to_string()));
+    let top = searcher.search(&q);
+
+    let years = top.terms(&TermsAggregation::new("year")).unwrap();
+    assert_eq!(vec![("1994", 2), ("2013", 1)], keys(&years));
+
+    let directors = top
+        .terms(&TermsAggregation::new("director").order(TermsOrder::KeyDesc))
+        .unwrap();
+    assert_eq!(
+        vec![("薛晓路", 1), ("张艺谋", 1), ("冯小刚", 1)],
+        keys(&directors)
+    );
+
+    let years = top
+        .terms(
+            &TermsAggregation::new("year")
+                .min_doc_count(0)
+                .order(TermsOrder::CountAsc)
+                .size(2),
+        )
+        .unwrap();
+    assert_eq!(vec![("1980", 0), ("2013", 1)], keys(&years));
+
+    let years = top
+        .terms(&TermsAggregation::new("year").min_doc_count(2))
+        .unwrap();
+    assert_eq!(vec![("1994", 2)], keys(&years));
+
+    // tokenized fields have no doc values.
+    assert!(top
+        .terms(&TermsAggregation::new("title"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_numeric_aggregations() {
+    let path = "/tmp/jets/TEST_NUMERIC_AGGS";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let movies = vec![
+        (1994, Some(8.0), 1358208000000i64), // 2013-01-15
+        (1996, Some(6.0), 1362182400000),    // 2013-03-02
+        (2005, Some(9.0), 1363737600000),    // 2013-03-20
+        (1999, None, 1404172800000),         // 2014-07-01
+    ];
+    for (i, (year, rating, released)) in movies.into_iter().enumerate() {
+        let mut doc = Document::builder()
+            .put("kind", DocValue::from("movie"), 0)
+            .put("year", DocValue::Long(year), 0)
+            .put("released", DocValue::Long(released), 0);
+        if let Some(n) = rating {
+            doc = doc.put("rating", DocValue::Double(n), 0);
+        }
+        writer.push(doc.build()).unwrap();
+        if i == 2 {
+            writer.flush().unwrap();
+        }
+    }
+    writer.flush().unwrap();
+
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let q = Query::from(Condition::term("kind".to_string(), "movie".to_string()));
+    let top = searcher.search(&q);
+    assert_eq!(
+        Some(6.0),
+        top.aggregate(&Aggregation::min("rating")).unwrap().value()
+    );
+    assert_eq!(
+        Some(9.0),
+        top.aggregate(&Aggregation::max("rating")).unwrap().value()
+    );
+    assert_eq!(
+        Some(23.0),
+        top.aggregate(&Aggregation::sum("rating")).unwrap().value()
+    );
+    assert_eq!(
+        Some(3.0),
+        top.aggregate(&Aggregation::value_count("rating"))
+            .unwrap()
+            .value()
+    );
+    let avg = top
+        .aggregate(&Aggregation::avg("rating"))
+        .unwrap()
+        .value()
+        .unwrap();
+    assert!((avg - 23.0 / 3.0).abs() < 1e-9);
+    let percentiles = top
+        .aggregate(&Aggregation::percentiles("rating", &[25.0, 50.0, 100.0]))
+        .unwrap();
+    assert_eq!(
+        &[(25.0, 7.0), (50.0, 8.0), (100.0, 9.0)],
+        percentiles.percentiles()
+    );
+
+    // numeric terms are ordered by value.
+    let years = top
+        .terms(&TermsAggregation::new("year").order(TermsOrder::KeyDesc))
+        .unwrap();
+    assert_eq!(
+        vec![("2005", 1), ("1999", 1), ("1996", 1), ("1994", 1)],
+        keys(&years)
+    );
+
+    let agg = HistogramAggregation::new("year", 5.0).sub("rating", Aggregation::avg("rating"));
+    let result = top.aggregate(&agg.into()).unwrap();
+    let buckets = result.buckets();
+    assert_eq!(
+        vec![("1990", 1), ("1995", 2), ("2000", 0), ("2005", 1)],
+        keys(buckets)
+    );
+    assert_eq!(Some(1995.0), buckets[1].get_number());
+    let avg = |b: &Bucket| b.get_aggregation("rating").unwrap().value();
+    assert_eq!(
+        vec![Some(8.0), Some(6.0), None, Some(9.0)],
+        buckets.iter().map(avg).collect::<Vec<_>>()
+    );
+
+    let agg = DateHistogramAggregation::new("released", DateInterval::Month).min_doc_count(1);
+    let result = top.aggregate(&agg.into()).unwrap();
+    assert_eq!(
+        vec![
+            ("2013-01-01T00:00:00Z", 1),
+            ("2013-03-01T00:00:00Z", 2),
+            ("2014-07-01T00:00:00Z", 1)
+        ],
+        keys(result.buckets())
+    );
+    let agg = DateHistogramAggregation::new("released", DateInterval::Quarter);
+    let result = top.aggregate(&agg.into()).unwrap();
+    assert_eq!(7, result.buckets().len());
+    assert_eq!(
+        vec![3, 0, 0, 0, 0, 0, 1],
+        result
+            .buckets()
+            .iter()
+            .map(|it| it.get_doc_count())
+            .collect::<Vec<_>>()
+    );
+    let agg = DateHistogramAggregation::new("released", DateInterval::Week).min_doc_count(1);
+    let result = top.aggregate(&agg.into()).unwrap();
+    assert_eq!("2013-03-18T00:00:00Z", result.buckets()[2].get_key());
+
+    // nested bucket aggregations.
+    let agg = TermsAggregation::new("kind").sub(
+        "by_year",
+        HistogramAggregation::new("year", 10.0).sub("max", Aggregation::max("rating")),
+    );
+    let result = top.aggregate(&agg.into()).unwrap();
+    let by_year = result.buckets()[0].get_aggregation("by_year").unwrap();
+    assert_eq!(vec![("1990", 3), ("2000", 1)], keys(by_year.buckets()));
+    let max = by_year.buckets()[0].get_aggregation("max").unwrap();
+    assert_eq!(Some(8.0), max.value());
+}
+
+#[test]
+fn test_aggregation_limits() {
+    let path = "/tmp/jets/TEST_AGGS_LIMITS";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let values = vec![(i64::MAX, 0.5), (i64::MAX - 1, f64::NAN), (1, 1_000_000.0)];
+    for (id, score) in values {
+        let doc = Document::builder()
+            .put("kind", DocValue::from("film"), 0)
+            .put("id", DocValue::Long(id), 0)
+            .put("score", DocValue::Double(score), 0)
+            .build();
+        writer.push(doc).unwrap();
+    }
+    writer.flush().unwrap();
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let top = searcher.search(&Query::from(Condition::term(
+        "kind".to_string(),
+        "film".to_string(),
+    )));
+
+    // longs are exact keys, not rounded through f64.
+    let ids = top
+        .terms(&TermsAggregation::new("id").order(TermsOrder::KeyDesc))
+        .unwrap();
+    let expected = [i64::MAX, i64::MAX - 1, 1];
+    assert_eq!(
+        expected.iter().map(|it| it.to_string()).collect::<Vec<_>>(),
+        ids.iter().map(|it| it.get_key()).collect::<Vec<_>>()
+    );
+    // NaN is in no bucket.
+    let scores = top.terms(&TermsAggregation::new("score")).unwrap();
+    assert_eq!(2, scores.len());
+
+    // empty buckets between 0 and 1000000 are too many.
+    let agg = HistogramAggregation::new("score", 1.0);
+    assert!(top.aggregate(&agg.into()).is_err());
+    let agg = HistogramAggregation::new("score", 1.0).max_buckets(2_000_000);
+    let result = top.aggregate(&agg.into()).unwrap();
+    assert_eq!(1_000_001, result.buckets().len());
+    let agg = HistogramAggregation::new("score", 1.0).min_doc_count(1);
+    let result = top.aggregate(&agg.into()).unwrap();
+    assert_eq!(vec![("0", 1), ("1000000", 1)], keys(result.buckets()));
+    let agg = HistogramAggregation::new("score", 1.0)
+        .min_doc_count(1)
+        .max_buckets(1);
+    assert!(top.aggregate(&agg.into()).is_err());
+    let agg = DateHistogramAggregation::new("id", DateInterval::Fixed(1));
+    assert!(top.aggregate(&agg.into()).is_err());
+}
@@ -0,0 +1,89 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+
+/// Text, position, offsets, kind, created by and removed by of an analyzed token.
+type Explained<'a> = (
+    &'a str,
+    u32,
+    usize,
+    usize,
+    &'a str,
+    &'a str,
+    Option<&'a str>,
+);
+
+#[test]
+fn test_analyzer_explain() {
+    let analyzer = Analyzer::builder()
+        .filter(SynonymFilter::parse("北大 => 北京 大学", true).unwrap())
+        .filter(
+            PinyinFilter::default()
+                .full(false)
+                .initials(false)
+                .syllables(false)
+                .max_combinations(1),
+        )
+        .build();
+    let explained = analyzer.explain(&JiebaTokenizer::default(), "北大的2020");
+    let found: Vec<Explained> = explained
+        .iter()
+        .map(|it| {
+            let t = it.get_token();
+            (
+                t.get_text(),
+                t.get_position(),
+                t.get_start(),
+                t.get_end(),
+                t.get_kind(),
+                it.get_created_by(),
+                it.get_removed_by(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                "北大",
+                0,
+                0,
+                6,
+                "<IDEOGRAPHIC>",
+                "tokenizer",
+                Some("SynonymFilter")
+            ),
+            ("北京", 0, 0, 6, "SYNONYM", "SynonymFilter", None),
+            ("beijing", 0, 0, 6, "PINYIN", "PinyinFilter", None),
+            (
+                "的",
+                1,
+                6,
+                9,
+                "<IDEOGRAPHIC>",
+                "tokenizer",
+                Some("stopwords")
+            ),
+            ("大学", 1, 0, 6, "SYNONYM", "SynonymFilter", None),
+            ("daxue", 1, 0, 6, "PINYIN", "PinyinFilter", None),
+            ("2020", 3, 9, 13, "<NUM>", "tokenizer", None),
+        ],
+        found
+    );
+    assert_eq!(
+        &vec!["SynonymFilter".to_string()],
+        explained.last().unwrap().get_changed_by()
+    );
+}
+
+#[test]
+fn test_index_writer_analyze() {
+    let writer = IndexWriter::open("/tmp/jets/TEST_ANALYZE", JiebaTokenizer::default()).unwrap();
+    let found = writer.analyze("title", "我爱北京天安门");
+    let removed: Vec<&str> = found
+        .iter()
+        .filter(|it| it.is_removed())
+        .map(|it| it.get_token().get_text())
+        .collect();
+    assert_eq!(vec!["我"], removed);
+}
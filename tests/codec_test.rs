@@ -0,0 +1,90 @@
+extern crate jets;
+
+use glob::glob;
+use jets::analysis::*;
+use jets::core::*;
+use std::fs;
+
+fn write_index(path: &str) {
+    let _ = fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    for i in 0..100 {
+        let doc = Document::builder()
+            .put("name", DocValue::Text(format!("foo_{}", i)), 0)
+            .put("star", DocValue::Double(i as f64), 0)
+            .build();
+        writer.push(doc).unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+fn segment(path: &str, ext: &str) -> String {
+    let it = glob(&format!("{}/_segment_*.{}", path, ext))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    it.to_str().unwrap().to_string()
+}
+
+fn open_err(path: &str) -> String {
+    match IndexReader::open(path) {
+        Ok(_) => "".to_string(),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn modify(file: &str, f: impl Fn(&mut Vec<u8>)) {
+    let mut b = fs::read(file).unwrap();
+    f(&mut b);
+    fs::write(file, b).unwrap();
+}
+
+#[test]
+fn test_corrupt_index() {
+    let path = "/tmp/jets/TEST_CODEC";
+    write_index(path);
+    let reader = IndexReader::open(path).unwrap();
+    reader.check_integrity().unwrap();
+    assert_eq!(1, reader.find("name", "foo_42").unwrap().len());
+    drop(reader);
+
+    // a flipped bit in postings is not read on open, it is found by the integrity check.
+    let index = segment(path, "index");
+    modify(&index, |b| {
+        let i = b.len() / 3;
+        b[i] ^= 0x10;
+    });
+    let reader = IndexReader::open(path).unwrap();
+    let err = reader.check_integrity().unwrap_err().to_string();
+    assert!(err.starts_with("corrupt index"), "{}", err);
+    assert!(err.contains("checksum mismatch"), "{}", err);
+    drop(reader);
+
+    // truncated segment
+    write_index(path);
+    let index = segment(path, "index");
+    modify(&index, |b| b.truncate(b.len() - 3));
+    assert!(open_err(path).starts_with("corrupt index"));
+
+    // doc values and commit points are verified on open.
+    write_index(path);
+    modify(&segment(path, "dv"), |b| {
+        let i = b.len() / 2;
+        b[i] ^= 0x01;
+    });
+    assert!(open_err(path).contains("checksum mismatch"));
+
+    write_index(path);
+    let commit = format!("{}/segments_1", path);
+    modify(&commit, |b| b[0] = 0);
+    assert!(open_err(path).contains("invalid magic"));
+
+    // header: magic, codec name, version.
+    write_index(path);
+    modify(&commit, |b| {
+        let i = 4 + 1 + b[4] as usize + 3;
+        b[i] = 99;
+    });
+    assert!(open_err(path).starts_with("unsupported version"));
+}
@@ -0,0 +1,86 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+#[test]
+fn test_collapse() {
+    let path = "/tmp/jets/TEST_COLLAPSE";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let films = vec![
+        ("肖申克的救赎", Some("shawshank"), 9.7),
+        ("肖申克的救赎 蓝光版", Some("shawshank"), 9.5),
+        ("肖申克的救赎 导演剪辑版", Some("shawshank"), 9.6),
+        ("霸王别姬", Some("farewell"), 9.6),
+        ("霸王别姬 修复版", Some("farewell"), 9.4),
+        ("阿甘正传", Some("gump"), 9.5),
+        ("无名", None, 8.0),
+    ];
+    for (i, (title, film, star)) in films.into_iter().enumerate() {
+        let mut doc = Document::builder()
+            .put("title", DocValue::from(title), 0)
+            .put("kind", DocValue::from("film"), 0)
+            .put("star", DocValue::Double(star), 0);
+        if let Some(film) = film {
+            doc = doc.put("film", DocValue::from(film), 0);
+        }
+        writer.push(doc.build()).unwrap();
+        if i == 3 {
+            writer.flush().unwrap();
+        }
+    }
+    writer.flush().unwrap();
+
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let films = || {
+        Query::from(Condition::term("kind".to_string(), "film".to_string()))
+            .sort(SortField::desc("star"))
+    };
+    let titles = |top: &TopDocs| -> Vec<String> {
+        top.documents()
+            .unwrap_or_default()
+            .iter()
+            .map(|it| it.get("title").unwrap().to_string())
+            .collect()
+    };
+
+    let top = searcher.search(&films().collapse(Collapse::new("film").inner_hits(2)));
+    assert_eq!(7, top.total_hits());
+    assert_eq!(Some(4), top.total_groups());
+    assert_eq!(
+        vec!["肖申克的救赎", "霸王别姬", "阿甘正传", "无名"],
+        titles(&top)
+    );
+    let groups = top.groups();
+    assert_eq!(
+        vec![Some("shawshank"), Some("farewell"), Some("gump"), None],
+        groups.iter().map(|it| it.get_key()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![3, 2, 1, 1],
+        groups.iter().map(|it| it.get_total()).collect::<Vec<_>>()
+    );
+    let inner: Vec<String> = groups[0]
+        .get_inner_hits()
+        .iter()
+        .map(|id| top.document(*id).unwrap().get("title").unwrap().to_string())
+        .collect();
+    assert_eq!(vec!["肖申克的救赎", "肖申克的救赎 导演剪辑版"], inner);
+
+    // pages are made of groups, and the totals are kept.
+    let top = searcher.search(&films().collapse(Collapse::new("film")).offset(1).limit(2));
+    assert_eq!(Some(4), top.total_groups());
+    assert_eq!(vec!["霸王别姬", "阿甘正传"], titles(&top));
+    assert_eq!(1, top.groups()[0].get_inner_hits().len());
+
+    let top = searcher.search(&films().offset(2).limit(3));
+    assert_eq!(7, top.total_hits());
+    assert_eq!(None, top.total_groups());
+    assert_eq!(
+        vec!["霸王别姬", "肖申克的救赎 蓝光版", "阿甘正传"],
+        titles(&top)
+    );
+    assert!(searcher.search(&films().offset(7)).documents().is_none());
+}
@@ -0,0 +1,84 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use std::fs;
+use std::path::Path;
+
+fn push(writer: &mut IndexWriter<JiebaTokenizer>, name: &str) {
+    let doc = Document::builder()
+        .put("name", DocValue::from(name), 0)
+        .build();
+    writer.push(doc).unwrap();
+}
+
+#[test]
+fn test_commit_point() {
+    let path = "/tmp/jets/TEST_COMMIT";
+    let _ = fs::remove_dir_all(path);
+    let mut writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    push(&mut writer, "foo");
+    writer.flush().unwrap();
+    push(&mut writer, "bar");
+    writer.flush().unwrap();
+    drop(writer);
+
+    // only the latest commit point is kept.
+    assert!(!Path::new(path).join("segments_1").exists());
+    assert!(Path::new(path).join("segments_2").exists());
+
+    // a torn commit point, a pending one and files of a crashed flush.
+    fs::write(Path::new(path).join("segments_3"), b"torn").unwrap();
+    fs::write(Path::new(path).join("pending_segments_4"), b"").unwrap();
+    let orphan = Path::new(path).join("_segment_00000009.index");
+    fs::write(&orphan, b"orphan").unwrap();
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(2, reader.num_docs());
+    assert_eq!(1, reader.find("name", "foo").unwrap().len());
+    assert_eq!(1, reader.find("name", "bar").unwrap().len());
+    drop(reader);
+
+    // writer continues from the latest valid commit, files of the skipped one are kept.
+    let mut writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    assert!(orphan.exists());
+    assert!(!Path::new(path).join("pending_segments_4").exists());
+    push(&mut writer, "baz");
+    writer.flush().unwrap();
+    drop(writer);
+    // the new commit is newer than the skipped one, and its segment does not reuse an id.
+    assert!(Path::new(path).join("segments_4").exists());
+    assert!(!Path::new(path).join("segments_3").exists());
+    assert!(!Path::new(path).join("segments_2").exists());
+    assert_eq!(b"orphan".to_vec(), fs::read(&orphan).unwrap());
+    assert!(Path::new(path).join("_segment_00000010.index").exists());
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(3, reader.num_docs());
+    assert_eq!(1, reader.find("name", "baz").unwrap().len());
+    assert_eq!(1, reader.find("name", "foo").unwrap().len());
+    drop(reader);
+
+    // unreferenced files are removed once no commit is skipped.
+    drop(IndexWriter::open(path, JiebaTokenizer::default()).unwrap());
+    assert!(!orphan.exists());
+}
+
+#[test]
+fn test_legacy_index() {
+    let path = "/tmp/jets/TEST_COMMIT_LEGACY";
+    let _ = fs::remove_dir_all(path);
+    fs::create_dir_all(Path::new(path).join("data")).unwrap();
+    fs::write(Path::new(path).join("METADATA"), b"legacy").unwrap();
+    let segment = Path::new(path).join("_segment_00000000.index");
+    fs::write(&segment, b"legacy").unwrap();
+
+    let e = IndexReader::open(path).err().unwrap();
+    assert!(e.to_string().contains("unsupported version"), "{}", e);
+    let e = IndexWriter::open(path, JiebaTokenizer::default())
+        .err()
+        .unwrap();
+    assert!(e.to_string().contains("unsupported version"), "{}", e);
+    // nothing is overwritten.
+    assert_eq!(b"legacy".to_vec(), fs::read(&segment).unwrap());
+}
@@ -0,0 +1,95 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_concurrent_push() {
+    assert_send_sync::<IndexWriter<JiebaTokenizer>>();
+    let path = "/tmp/jets/TEST_CONCURRENT";
+    let _ = fs::remove_dir_all(path);
+    let config = IndexWriterConfig::default()
+        .wal(SyncPolicy::Never)
+        .max_buffered_docs(Some(50));
+    let writer =
+        Arc::new(IndexWriter::open_with_config(path, JiebaTokenizer::default(), config).unwrap());
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let writer = writer.clone();
+            thread::spawn(move || {
+                for i in 0..120 {
+                    let doc = Document::builder()
+                        .put("name", DocValue::Text(format!("doc_{}_{}", t, i)), 0)
+                        .put("thread", DocValue::Long(t), 0)
+                        .put("title", DocValue::from("北京欢迎你"), FLAG_TOKENIZED)
+                        .build();
+                    writer.push(doc).unwrap();
+                }
+            })
+        })
+        .collect();
+    for it in handles {
+        it.join().unwrap();
+    }
+    assert_eq!(480, writer.counter());
+    // every buffer is flushed into a segment of its own.
+    writer.flush().unwrap();
+    let stats = writer.stats();
+    assert_eq!(480, stats.get_flushed_docs());
+    assert_eq!(0, stats.get_buffered_docs());
+    assert!(stats.get_flushes() >= 10);
+    drop(writer);
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(480, reader.num_docs());
+    assert_eq!(480, reader.find("title", "北京").unwrap().len());
+    for t in 0..4 {
+        assert_eq!(120, reader.find("thread", DocValue::Long(t)).unwrap().len());
+        for i in [0, 49, 50, 119] {
+            let name = format!("doc_{}_{}", t, i);
+            let ids = reader.find("name", name.as_str()).unwrap();
+            assert_eq!(1, ids.len());
+            let found = reader.document(ids[0]).unwrap();
+            assert_eq!(Some(&DocValue::Text(name)), found.get("name"));
+            assert_eq!(Some(&DocValue::Long(t)), found.get("thread"));
+        }
+    }
+    // the log is dropped once all documents are committed.
+    assert_eq!(0, fs::metadata(format!("{}/wal.log", path)).unwrap().len());
+}
+
+#[test]
+fn test_concurrent_reader() {
+    let path = "/tmp/jets/TEST_CONCURRENT_NRT";
+    let _ = fs::remove_dir_all(path);
+    let writer = Arc::new(IndexWriter::open(path, JiebaTokenizer::default()).unwrap());
+    let handles: Vec<_> = (0..3)
+        .map(|t| {
+            let writer = writer.clone();
+            thread::spawn(move || {
+                for i in 0..50 {
+                    let doc = Document::builder()
+                        .put("name", DocValue::Text(format!("{}_{}", t, i)), 0)
+                        .build();
+                    writer.push(doc).unwrap();
+                    if i % 10 == 0 {
+                        let reader = writer.reader().unwrap();
+                        assert!(reader.num_docs() > i);
+                    }
+                }
+            })
+        })
+        .collect();
+    for it in handles {
+        it.join().unwrap();
+    }
+    assert_eq!(150, writer.reader().unwrap().num_docs());
+    writer.flush().unwrap();
+    drop(writer);
+    assert_eq!(150, IndexReader::open(path).unwrap().num_docs());
+}
@@ -0,0 +1,107 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+#[test]
+fn test_doc_values_presence() {
+    let path = "/tmp/jets/TEST_DOC_VALUES";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    for i in 0..1000i64 {
+        let mut doc = Document::builder()
+            .put("kind", DocValue::from("all"), 0)
+            .put("dense", DocValue::Long(i - 500), 0);
+        // sparse: 10 docs, bitmap: every other doc.
+        if i % 100 == 0 {
+            doc = doc.put("sparse", DocValue::from(format!("tag_{}", i / 100 % 3)), 0);
+        }
+        if i % 2 == 0 {
+            doc = doc.put("half", DocValue::Double(i as f64 / 2.0), 0);
+        }
+        writer.push(doc.build()).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let q = Query::from(Condition::term("kind".to_string(), "all".to_string()));
+    let top = searcher.search(&q);
+    assert_eq!(
+        Some(-500.0),
+        top.aggregate(&Aggregation::min("dense")).unwrap().value()
+    );
+    assert_eq!(
+        Some(499.0),
+        top.aggregate(&Aggregation::max("dense")).unwrap().value()
+    );
+    assert_eq!(
+        Some(500.0),
+        top.aggregate(&Aggregation::value_count("half"))
+            .unwrap()
+            .value()
+    );
+    assert_eq!(
+        Some(499.0),
+        top.aggregate(&Aggregation::max("half")).unwrap().value()
+    );
+    let tags: Vec<(String, u64)> = top
+        .terms(&TermsAggregation::new("sparse"))
+        .unwrap()
+        .iter()
+        .map(|it| (it.get_key().to_string(), it.get_doc_count()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("tag_0".to_string(), 4),
+            ("tag_1".to_string(), 3),
+            ("tag_2".to_string(), 3)
+        ],
+        tags
+    );
+
+    let q = Query::from(Condition::term("kind".to_string(), "all".to_string()))
+        .sort(SortField::asc("sparse"))
+        .sort(SortField::desc("dense"));
+    let docs = searcher.search(&q).documents().unwrap();
+    let first: Vec<String> = docs
+        .iter()
+        .take(5)
+        .map(|it| it.get("dense").unwrap().to_string())
+        .collect();
+    assert_eq!(vec!["400", "100", "-200", "-500", "200"], first);
+}
+
+#[test]
+fn test_num_docs_without_doc_values() {
+    let path = "/tmp/jets/TEST_DOC_VALUES_MISSING";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    for i in 0..300 {
+        let doc = Document::builder()
+            .put("name", DocValue::from(format!("name_{}", i)), 0)
+            .build();
+        writer.push(doc).unwrap();
+    }
+    writer.flush().unwrap();
+    for i in 0..20 {
+        let doc = Document::builder()
+            .put("kind", DocValue::from(format!("kind_{}", i % 3)), 0)
+            .build();
+        writer.push(doc).unwrap();
+    }
+    writer.flush().unwrap();
+    drop(writer);
+    assert_eq!(320, IndexReader::open(path).unwrap().num_docs());
+
+    // segments written before doc values are counted by their postings.
+    for entry in std::fs::read_dir(path).unwrap() {
+        let entry = entry.unwrap().path();
+        if entry.extension().is_some_and(|ext| ext == "dv") {
+            std::fs::remove_file(entry).unwrap();
+        }
+    }
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(320, reader.num_docs());
+    assert_eq!(1, reader.find("name", "name_299").unwrap().len());
+}
@@ -0,0 +1,75 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+fn term(word: &str) -> Condition {
+    Condition::term("tag".to_string(), word.to_string())
+}
+
+#[test]
+fn test_explain() {
+    let path = "/tmp/jets/TEST_EXPLAIN";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let docs = vec![
+        vec!["a", "b"],
+        vec!["a"],
+        vec!["b", "c"],
+        vec!["c"],
+        vec!["a"],
+    ];
+    for tags in docs {
+        let mut doc = Document::builder();
+        for it in tags {
+            doc = doc.put("tag", DocValue::from(it), 0);
+        }
+        writer.push(doc.build()).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let mut searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let q = Query::from(Condition::group(Operator::OR, vec![term("a"), term("b")]));
+    let top = searcher.search(&q);
+    let ids: Vec<u64> = top
+        .documents()
+        .unwrap()
+        .iter()
+        .map(|it| it.get_id())
+        .collect();
+    // rare terms score higher, matched clauses are summed.
+    assert_eq!(vec![0, 2, 1, 4], ids);
+
+    let explained = searcher.explain(&q, 0);
+    assert!(explained.is_match());
+    assert_eq!(top.score(0).unwrap(), explained.get_value());
+    assert_eq!(2, explained.get_details().len());
+    let idf_a = (1.0f32 + 2.5 / 3.5).ln();
+    let idf_b = (1.0f32 + 3.5 / 2.5).ln();
+    assert!((explained.get_value() - idf_a - idf_b).abs() < 1e-5);
+    let stats = explained.get_details()[0].get_details()[0].get_details();
+    assert_eq!(3.0, stats[0].get_value());
+    assert_eq!(5.0, stats[1].get_value());
+
+    let explained = searcher.explain(&q, 2);
+    assert_eq!(top.score(2).unwrap(), explained.get_value());
+    assert!(!explained.get_details()[0].is_match());
+    assert!(explained.get_details()[1].is_match());
+
+    let q = Query::from(Condition::group(Operator::AND, vec![term("a"), term("c")]));
+    let explained = searcher.explain(&q, 3);
+    assert!(!explained.is_match());
+    assert_eq!(0.0, explained.get_value());
+    assert!(explained.to_string().contains("not all clauses matched"));
+
+    // synonyms are explained as alternative paths.
+    let synonyms = SynonymFilter::parse("z, a", true).unwrap();
+    searcher.set_analyzer("tag", Analyzer::builder().filter(synonyms).build());
+    let q = Query::from(term("z"));
+    let explained = searcher.explain(&q, 1);
+    assert!(explained.is_match());
+    assert_eq!(2, explained.get_details().len());
+    assert!((explained.get_value() - idf_a).abs() < 1e-5);
+    assert_eq!(searcher.search(&q).score(1).unwrap(), explained.get_value());
+}
@@ -0,0 +1,141 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use std::fs;
+
+fn open(path: &str, config: IndexWriterConfig) -> IndexWriter<JiebaTokenizer> {
+    let _ = fs::remove_dir_all(path);
+    IndexWriter::open_with_config(path, JiebaTokenizer::default(), config).unwrap()
+}
+
+fn doc(name: String) -> Document {
+    Document::builder()
+        .put("name", DocValue::Text(name), 0)
+        .build()
+}
+
+#[test]
+fn test_flush_by_doc_count() {
+    let path = "/tmp/jets/TEST_FLUSH_DOCS";
+    let config = IndexWriterConfig::default().max_buffered_docs(Some(10));
+    let writer = open(path, config);
+    for i in 0..25 {
+        writer.push(doc(format!("foo_{}", i))).unwrap();
+    }
+    let stats = writer.stats();
+    assert_eq!(2, stats.get_flushes());
+    assert_eq!(2, stats.get_auto_flushes());
+    assert_eq!(20, stats.get_flushed_docs());
+    assert_eq!(5, stats.get_buffered_docs());
+    assert!(stats.get_buffered_bytes() > 0);
+    // without the log, flushed segments are committed while others are buffered.
+    assert_eq!(20, IndexReader::open(path).unwrap().num_docs());
+
+    writer.flush().unwrap();
+    let stats = writer.stats();
+    assert_eq!(3, stats.get_flushes());
+    assert_eq!(2, stats.get_auto_flushes());
+    assert_eq!(25, stats.get_flushed_docs());
+    assert_eq!(0, stats.get_buffered_docs());
+    assert_eq!(0, stats.get_buffered_bytes());
+    assert!(stats.get_elapsed() >= stats.get_last_elapsed());
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(25, reader.num_docs());
+    for i in [0, 9, 10, 24] {
+        let ids = reader.find("name", format!("foo_{}", i)).unwrap();
+        assert_eq!(1, ids.len());
+        let found = reader.document(ids[0]).unwrap();
+        assert_eq!(Some(&DocValue::Text(format!("foo_{}", i))), found.get("name"));
+    }
+}
+
+#[test]
+fn test_flush_by_ram() {
+    let path = "/tmp/jets/TEST_FLUSH_RAM";
+    let budget = 16 * 1024;
+    let config = IndexWriterConfig::default().ram_buffer_size(Some(budget));
+    let writer = open(path, config);
+    for i in 0..40 {
+        writer.push(doc(format!("{}_{}", "x".repeat(1000), i))).unwrap();
+        assert!(writer.stats().get_buffered_bytes() < budget as u64);
+    }
+    let flushes = writer.stats().get_auto_flushes();
+    assert!(flushes >= 2, "auto flushes: {}", flushes);
+    writer.flush().unwrap();
+    assert_eq!(40, writer.stats().get_flushed_docs());
+    assert!(writer.stats().get_flushed_bytes() >= 40 * 1000);
+    assert_eq!(40, IndexReader::open(path).unwrap().num_docs());
+    drop(writer);
+
+    // no automatic flush when disabled.
+    let config = IndexWriterConfig::default().ram_buffer_size(None);
+    let writer = open(path, config);
+    for i in 0..40 {
+        writer.push(doc(format!("{}_{}", "x".repeat(1000), i))).unwrap();
+    }
+    assert_eq!(0, writer.stats().get_flushes());
+    assert_eq!(40, writer.stats().get_buffered_docs());
+}
+
+#[test]
+fn test_failed_flush() {
+    let path = "/tmp/jets/TEST_FLUSH_FAILED";
+    let config = IndexWriterConfig::default()
+        .wal(SyncPolicy::Always)
+        .max_buffered_docs(Some(10));
+    let writer = open(path, config);
+    // segment files can not be created where directories are.
+    let blocked: Vec<String> = (0..2)
+        .map(|i| format!("{}/_segment_{:08}.index", path, i))
+        .collect();
+    for it in blocked.iter() {
+        fs::create_dir_all(it).unwrap();
+    }
+    for i in 0..9 {
+        writer.push(doc(format!("foo_{}", i))).unwrap();
+    }
+    assert!(writer.push(doc("foo_9".to_string())).is_err());
+    assert!(writer.flush().is_err());
+    assert_eq!(0, writer.stats().get_flushes());
+    assert_eq!(10, writer.stats().get_buffered_docs());
+    for it in blocked.iter() {
+        fs::remove_dir(it).unwrap();
+    }
+
+    // documents of failed flushes are still buffered.
+    writer.flush().unwrap();
+    assert_eq!(10, writer.stats().get_flushed_docs());
+    assert_eq!(0, fs::metadata(format!("{}/wal.log", path)).unwrap().len());
+    writer.push(doc("bar".to_string())).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(0, fs::metadata(format!("{}/wal.log", path)).unwrap().len());
+    drop(writer);
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(11, reader.num_docs());
+    assert_eq!(1, reader.find("name", "foo_9").unwrap().len());
+}
+
+#[test]
+fn test_rejected_document() {
+    let path = "/tmp/jets/TEST_FLUSH_REJECTED";
+    let writer = open(path, IndexWriterConfig::default());
+    writer.push(doc("foo".to_string())).unwrap();
+    // "name" is text, the whole document is rejected.
+    let bad = Document::builder()
+        .put("title", DocValue::from("stale"), 0)
+        .put("name", DocValue::Long(1), 0)
+        .build();
+    assert!(writer.push(bad).is_err());
+    writer.push(doc("bar".to_string())).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(2, reader.num_docs());
+    let ids = reader.find("name", "bar").unwrap();
+    assert_eq!(1, ids.len());
+    assert_eq!(None, reader.document(ids[0]).unwrap().get("title"));
+}
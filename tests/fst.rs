@@ -4,6 +4,7 @@ extern crate jets;
 
 use jets::io::{MemReader, MemWriter, Reader, Writer};
 use jets::utils::fst::*;
+use jets::utils::FOR;
 use rand::prelude::*;
 use std::collections::HashMap;
 
@@ -108,6 +109,69 @@ fn test_fst_for() {
     for it in vec!["foo", "bar", "baz"] {
         info!("seek {}: {:?}", it, fst2.get(it).unwrap());
     }
+    assert_eq!(vec![1, 2, 3], fst2.get("foo").unwrap());
+    assert_eq!(vec![2, 3, 4], fst2.get("bar").unwrap());
+    assert_eq!(vec![5], fst2.get("baz").unwrap());
+}
+
+#[test]
+fn test_for_decode() {
+    // blocks which are not aligned to bytes.
+    for n in [1, 3, 7, 100, 128, 129, 300] {
+        let ids: Vec<u32> = (0..n).map(|it| it * 3 + 1).collect();
+        let mut bs = FOR::from(&ids).bytes().unwrap();
+        let decoded: Vec<u32> = FOR::decode(&mut bs).unwrap().iter().collect();
+        assert_eq!(ids, decoded);
+        assert!(bs.is_empty());
+
+        let bs = FOR::from(&ids).bytes().unwrap();
+        let mut pos = 0;
+        let decoded: Vec<u32> = FOR::decode_slice(&bs[..], &mut pos)
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(ids, decoded);
+        assert_eq!(bs.len(), pos);
+        assert!(FOR::decode_slice(&bs[..bs.len() - 1], &mut 0).is_err());
+    }
+}
+
+#[test]
+fn test_fst_mapped() {
+    init();
+    let mut rng = rand::thread_rng();
+    let mut keys: Vec<String> = (0..1000).map(|_| rand_str(&mut rng, 16)).collect();
+    keys.sort();
+    keys.dedup();
+    let mut bu = FST::builder(OutputsU64);
+    let mut offset = 4u64;
+    let mut data: HashMap<String, u64> = HashMap::new();
+    for k in keys.iter() {
+        bu = bu.push(k, offset);
+        data.insert(k.clone(), offset);
+        offset += rng.gen_range(1, 100);
+    }
+    let fst = bu.build();
+
+    let mut writer = MemWriter::new();
+    let size = fst.save_mapped(&mut writer).unwrap();
+    let bs = writer.to_bytes();
+    assert_eq!(size, bs.len());
+
+    let mapped = MappedFST::open(&bs[..]).unwrap();
+    for (k, expect) in data.iter() {
+        assert_eq!(Some(*expect), mapped.get(k).unwrap());
+        let missing = format!("{}{{", k);
+        assert_eq!(None, mapped.get(&missing).unwrap());
+    }
+    assert_eq!(None, mapped.get("").unwrap());
+    assert_eq!(None, mapped.get("A").unwrap());
+
+    // nodes are written children first, the root is at the end.
+    let nodes = &bs[..bs.len() - 8];
+    let truncated = MappedFST::with_root(&nodes[..mapped.get_root()], mapped.get_root());
+    assert!(truncated.get(keys[0].as_str()).is_err());
+    assert!(MappedFST::open(&bs[bs.len() - 8..]).is_err());
 }
 
 fn rand_str(rng: &mut ThreadRng, max_len: usize) -> String {
@@ -0,0 +1,200 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+fn term(field: &str, word: &str) -> Condition {
+    Condition::term(field.to_string(), word.to_string())
+}
+
+#[test]
+fn test_function_score() {
+    let path = "/tmp/jets/TEST_FUNCTION";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    // name, star, year, (lat, lon)
+    let films = vec![
+        ("a", Some(7.0), 1994, (39.9, 116.4)),
+        ("b", Some(9.5), 2013, (31.2, 121.5)),
+        ("c", None, 2001, (22.5, 114.1)),
+        ("d", Some(8.0), 2019, (39.9, 116.5)),
+    ];
+    for (name, star, year, (lat, lon)) in films {
+        let mut doc = Document::builder()
+            .put("name", DocValue::from(name), 0)
+            .put("kind", DocValue::from("film"), 0)
+            .put("year", DocValue::Long(year), 0)
+            .put("lat", DocValue::Double(lat), 0)
+            .put("lon", DocValue::Double(lon), 0);
+        if let Some(n) = star {
+            doc = doc.put("star", DocValue::Double(n), 0);
+        }
+        writer.push(doc.build()).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let names = |cond: Condition| -> Vec<String> {
+        searcher
+            .search(&Query::from(cond))
+            .documents()
+            .unwrap()
+            .iter()
+            .map(|it| it.get("name").unwrap().to_string())
+            .collect()
+    };
+
+    // popular films first, films without star keep the query score.
+    let popular = || {
+        FunctionScore::new(term("kind", "film")).function(ScoreFunction::field_value_factor(
+            FieldValueFactor::new("star").modifier(Modifier::Ln1p),
+        ))
+    };
+    assert_eq!(vec!["b", "d", "a", "c"], names(popular().into()));
+    let q = Query::from(Condition::from(
+        popular()
+            .boost_mode(BoostMode::Replace)
+            .score_mode(ScoreMode::Sum),
+    ));
+    let top = searcher.search(&q);
+    assert!((top.score(1).unwrap() - 10.5f32.ln()).abs() < 1e-5);
+    let explained = searcher.explain(&q, 1);
+    assert!(explained.is_match());
+    assert_eq!(top.score(1).unwrap(), explained.get_value());
+    assert_eq!(2, explained.get_details().len());
+
+    // newest first: gauss decay is 0.5 at scale away from origin.
+    let recent = FunctionScore::new(term("kind", "film"))
+        .function(ScoreFunction::decay(
+            Decay::number(DecayKind::Gauss, "year", 2020.0, 10.0).unwrap(),
+        ))
+        .boost_mode(BoostMode::Replace);
+    let q = Query::from(Condition::from(recent));
+    let top = searcher.search(&q);
+    assert!((top.score(1).unwrap() - 0.5f32.powf(0.49)).abs() < 1e-5);
+    assert_eq!(
+        vec![3, 1, 2, 0],
+        top.documents()
+            .unwrap()
+            .iter()
+            .map(|it| it.get_id())
+            .collect::<Vec<u64>>()
+    );
+
+    // nearest to beijing first, linear decay is 0.5 at scale away.
+    let near = FunctionScore::new(term("kind", "film"))
+        .function(ScoreFunction::decay(
+            Decay::geo(DecayKind::Linear, "lat", "lon", (39.9, 116.4), 1000.0)
+                .unwrap()
+                .offset(10.0),
+        ))
+        .boost_mode(BoostMode::Replace);
+    let q = Query::from(Condition::from(near));
+    let top = searcher.search(&q);
+    assert_eq!(1.0, top.score(0).unwrap());
+    assert_eq!(1.0, top.score(3).unwrap());
+    assert!(top.score(1).unwrap() > 0.4 && top.score(1).unwrap() < 0.5);
+    assert!(top.score(2).unwrap() < 0.1);
+
+    // random scores are stable for the same seed.
+    let random = |seed| {
+        Condition::from(
+            FunctionScore::new(term("kind", "film"))
+                .function(ScoreFunction::random(seed))
+                .boost_mode(BoostMode::Replace),
+        )
+    };
+    assert_eq!(names(random(42)), names(random(42)));
+    let top = searcher.search(&Query::from(random(7)));
+    for id in 0..4 {
+        let score = top.score(id).unwrap();
+        assert!((0.0..1.0).contains(&score));
+    }
+
+    // logarithms of values not greater than 0 stay finite.
+    let log = FunctionScore::new(term("kind", "film"))
+        .function(ScoreFunction::field_value_factor(
+            FieldValueFactor::new("year")
+                .factor(-1.0)
+                .modifier(Modifier::Ln),
+        ))
+        .boost_mode(BoostMode::Replace);
+    let top = searcher.search(&Query::from(Condition::from(log)));
+    for id in 0..4 {
+        assert!(top.score(id).unwrap().is_finite());
+    }
+
+    // square roots of negative values and reciprocals of 0 stay finite.
+    for (factor, modifier) in [(-1.0, Modifier::Sqrt), (0.0, Modifier::Reciprocal)] {
+        let f = FunctionScore::new(term("kind", "film"))
+            .function(ScoreFunction::field_value_factor(
+                FieldValueFactor::new("year")
+                    .factor(factor)
+                    .modifier(modifier),
+            ))
+            .boost_mode(BoostMode::Replace);
+        let top = searcher.search(&Query::from(Condition::from(f)));
+        for id in 0..4 {
+            assert_eq!(0.0, top.score(id).unwrap());
+        }
+    }
+
+    let decay = || Decay::number(DecayKind::Exp, "year", 2020.0, 10.0).unwrap();
+    assert!(decay().decay(0.3).is_ok());
+    for it in [0.0, 1.0, -0.5, f64::NAN] {
+        assert!(decay().decay(it).is_err());
+    }
+    for it in [0.0, -10.0, f64::NAN, f64::INFINITY] {
+        assert!(Decay::number(DecayKind::Exp, "year", 2020.0, it).is_err());
+        assert!(Decay::geo(DecayKind::Gauss, "lat", "lon", (39.9, 116.4), it).is_err());
+    }
+    let zero = std::time::Duration::from_secs(0);
+    assert!(Decay::date(DecayKind::Linear, "year", 0, zero).is_err());
+}
+
+#[test]
+fn test_boost() {
+    let path = "/tmp/jets/TEST_BOOST";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    for tags in [vec!["a"], vec!["b"], vec!["a", "b"], vec!["c"]] {
+        let mut doc = Document::builder();
+        for it in tags {
+            doc = doc.put("tag", DocValue::from(it), 0);
+        }
+        writer.push(doc.build()).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let ids = |q: &Query| -> Vec<u64> {
+        searcher
+            .search(q)
+            .documents()
+            .unwrap()
+            .iter()
+            .map(|it| it.get_id())
+            .collect()
+    };
+    let q = Query::from(Condition::group(
+        Operator::OR,
+        vec![term("tag", "a"), term("tag", "b").boost(3.0)],
+    ));
+    assert_eq!(vec![2, 1, 0], ids(&q));
+    let explained = searcher.explain(&q, 1);
+    assert_eq!(searcher.search(&q).score(1).unwrap(), explained.get_value());
+    assert!(explained.to_string().contains("boost, product of:"));
+
+    let q = Query::from(
+        Condition::group(Operator::OR, vec![term("tag", "a"), term("tag", "c")]).boost(0.5),
+    );
+    let top = searcher.search(&q);
+    let idf = (1.0f32 + 2.5 / 2.5).ln();
+    assert!((top.score(0).unwrap() - idf * 0.5).abs() < 1e-5);
+
+    let q = Query::from(Condition::from(FunctionScore::new(term("tag", "a"))).boost(2.0));
+    let top = searcher.search(&q);
+    assert!((top.score(0).unwrap() - idf * 2.0).abs() < 1e-5);
+    assert_eq!(top.score(0).unwrap(), searcher.explain(&q, 0).get_value());
+}
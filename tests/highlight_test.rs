@@ -0,0 +1,101 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+fn term(word: &str) -> Condition {
+    Condition::term("title".to_string(), word.to_string())
+}
+
+#[test]
+fn test_token_offsets() {
+    let input = "我爱北京天安门";
+    let tokens = Analyzer::default().analyze(&JiebaTokenizer::default(), input);
+    for it in tokens.iter() {
+        assert_eq!(it.get_text(), &input[it.get_start()..it.get_end()]);
+    }
+    let tokens = PinyinFilter::default().filter(tokens);
+    let found = tokens.iter().find(|it| it.get_text() == "beijing").unwrap();
+    assert_eq!("北京", &input[found.get_start()..found.get_end()]);
+}
+
+#[test]
+fn test_highlight() {
+    let highlighter = Highlighter::builder(JiebaTokenizer::default())
+        .pre_tag("[")
+        .post_tag("]")
+        .fragment_size(6)
+        .max_fragments(2)
+        .build();
+    let q = Query::from(Condition::group(
+        Operator::OR,
+        vec![term("北京"), term("长城")],
+    ));
+    let text = "北京有长城，上海有外滩，西安有兵马俑，北京还有故宫";
+    let found = highlighter.highlight_text(&q, "title", text);
+    assert_eq!(vec!["[北京]有[长城]，", "俑，[北京]还有"], found);
+
+    let doc = Document::builder()
+        .put("title", DocValue::from("恶魔的眼睛"), FLAG_TOKENIZED)
+        .put("year", DocValue::from("1998"), 0)
+        .build();
+    let highlighter = Highlighter::builder(JiebaTokenizer::default())
+        .fragment_size(0)
+        .analyzer(
+            "title",
+            Analyzer::builder().filter(PinyinFilter::default()).build(),
+        )
+        .build();
+    let found = highlighter.highlight(&Query::from(term("emo")), &doc);
+    assert_eq!(1, found.len());
+    assert_eq!(vec!["<em>恶魔</em>的眼睛"], found["title"]);
+}
+
+#[test]
+fn test_highlight_crossing_fragment() {
+    let highlighter = Highlighter::builder(JiebaTokenizer::default())
+        .pre_tag("[")
+        .post_tag("]")
+        .build();
+    let q = Query::from(Condition::group(
+        Operator::OR,
+        vec![term("北京"), term("上海")],
+    ));
+    // 上海 starts in the fragment of 北京 and ends after it.
+    let text = format!("北京{}上海{}", "的".repeat(97), "的".repeat(19));
+    let found = highlighter.highlight_text(&q, "title", &text);
+    assert_eq!(vec![format!("[北京]{}[上海]", "的".repeat(97))], found);
+}
+
+#[test]
+fn test_highlight_stored() {
+    let path = "/tmp/jets/TEST_HIGHLIGHT";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let doc = Document::builder()
+        .put("title", DocValue::from("北京有长城"), FLAG_TOKENIZED)
+        .put("code", DocValue::from("北京"), 0)
+        .build();
+    writer.push(doc).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let reader = IndexReader::open(path).unwrap();
+    let id = reader.find("title", "北京").unwrap()[0];
+    let doc = reader.document(id).unwrap();
+    let q = Query::from(Condition::group(
+        Operator::OR,
+        vec![
+            term("北京"),
+            Condition::term("code".to_string(), "北京".to_string()),
+        ],
+    ));
+    // fields not tokenized are not highlighted.
+    let highlighter = Highlighter::builder(JiebaTokenizer::default())
+        .fragment_size(0)
+        .build();
+    let found = highlighter.highlight(&q, &doc);
+    assert_eq!(1, found.len());
+    assert_eq!(vec!["<em>北京</em>有长城"], found["title"]);
+}
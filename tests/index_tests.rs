@@ -17,7 +17,7 @@ static AMOUNT: u64 = 10000;
 #[test]
 fn test_index_write() {
     init();
-    let mut writer = IndexWriter::open(DIR, JiebaTokenizer::default()).unwrap();
+    let writer = IndexWriter::open(DIR, JiebaTokenizer::default()).unwrap();
     for i in 0..AMOUNT {
         writer.push(mock_doc(i)).unwrap();
     }
@@ -58,7 +58,7 @@ fn test_text_index_write() {
         "北京有长城",
     ];
     let path = "/tmp/jets/TEST_TEXT";
-    let mut writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
     for i in 0..inputs.len() {
         let doc = Document::builder()
             .put("content", DocValue::from(inputs[i]), FLAG_TOKENIZED)
@@ -83,24 +83,53 @@ fn test_fulltext_index_searcher() {
         info!("---------------------------------");
     };
 
-    submit(Query::from(Condition::Term(
+    submit(Query::from(Condition::term(
         "content".to_string(),
         "长城".to_string(),
     )));
 
-    submit(Query::from(Condition::Group(
+    submit(Query::from(Condition::group(
         Operator::OR,
         vec![
-            Condition::Term("content".to_string(), "上海".to_string()),
-            Condition::Term("content".to_string(), "北京".to_string()),
+            Condition::term("content".to_string(), "上海".to_string()),
+            Condition::term("content".to_string(), "北京".to_string()),
         ],
     )));
 
-    submit(Query::from(Condition::Group(
+    submit(Query::from(Condition::group(
         Operator::AND,
         vec![
-            Condition::Term("content".to_string(), "长城".to_string()),
-            Condition::Term("content".to_string(), "北京".to_string()),
+            Condition::term("content".to_string(), "长城".to_string()),
+            Condition::term("content".to_string(), "北京".to_string()),
         ],
     )));
 }
+
+#[test]
+fn test_document_fields() {
+    init();
+    let path = "/tmp/jets/TEST_DOCUMENT_FIELDS";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    for segment in 0..2 {
+        for i in 0..3 {
+            writer.push(mock_doc(segment * 10 + i)).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+    drop(writer);
+
+    let reader = IndexReader::open(path).unwrap();
+    for id in [0, 2, 10, 12] {
+        let ids = reader.find("name", format!("foo_{}", id)).unwrap();
+        assert_eq!(1, ids.len());
+        // all fields of a document share its id.
+        let doc = reader.document(ids[0]).unwrap();
+        assert_eq!(Some(&DocValue::Text(format!("foo_{}", id))), doc.get("name"));
+        assert_eq!(
+            Some(&DocValue::Text(format!("bar_{}", id))),
+            doc.get("nickname")
+        );
+        assert_eq!(ids, reader.find("nickname", format!("bar_{}", id)).unwrap());
+    }
+}
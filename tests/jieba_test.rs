@@ -1,3 +1,5 @@
+use jets::analysis::{CutMode, JiebaTokenizer, Tokenizer};
+use jets::core::*;
 use jieba_rs::Jieba;
 
 #[test]
@@ -27,3 +29,88 @@ fn test_dup() {
         .collect();
     println!("ids: {:?}", ids);
 }
+
+#[test]
+fn test_jieba_tokenizer_builder() {
+    let input = "周星驰主演了功夫熊猫侠";
+    let tokenizer = JiebaTokenizer::builder()
+        .mode(CutMode::Precise)
+        .word("功夫熊猫侠", Some(100_000), Some("nz"))
+        .build()
+        .unwrap();
+    let words = tokenizer.tokenize(input);
+    assert!(words.contains(&"功夫熊猫侠"));
+
+    // the dictionary is stored by the commit, and loaded by writers and readers opened later.
+    let dir = "/tmp/jets/TEST_JIEBA";
+    let _ = std::fs::remove_dir_all(dir);
+    let writer = IndexWriter::open(dir, tokenizer).unwrap();
+    let doc = Document::builder()
+        .put("title", DocValue::from(input), FLAG_TOKENIZED)
+        .build();
+    writer.push(doc).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let dictionary = IndexReader::read_dictionary(dir, JiebaTokenizer::KIND).unwrap();
+    let reopen = JiebaTokenizer::from_dictionary(&dictionary.unwrap()).unwrap();
+    assert_eq!(CutMode::Precise, reopen.get_mode());
+    assert_eq!(words, reopen.tokenize(input));
+    let reader = IndexReader::open(dir).unwrap();
+    assert_eq!(words, reader.get_tokenizer().unwrap().tokenize(input));
+    assert_eq!(1, reader.find("title", "功夫熊猫侠").unwrap().len());
+    drop(reader);
+    let writer = IndexWriter::open(dir, JiebaTokenizer::default()).unwrap();
+    let analyzed: Vec<String> = writer
+        .analyze("title", input)
+        .iter()
+        .map(|it| it.get_token().get_text().to_string())
+        .collect();
+    assert!(analyzed.contains(&"功夫熊猫侠".to_string()));
+}
+
+#[test]
+fn test_jieba_default_dictionary() {
+    let dir = "/tmp/jets/TEST_JIEBA_DEFAULT";
+    let _ = std::fs::remove_dir_all(dir);
+    let writer = IndexWriter::open(dir, JiebaTokenizer::default()).unwrap();
+    let doc = Document::builder()
+        .put("title", DocValue::from("功夫熊猫"), FLAG_TOKENIZED)
+        .build();
+    writer.push(doc).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    assert!(IndexReader::open(dir).unwrap().get_tokenizer().is_none());
+}
+
+/// Splits on whitespace, with a dictionary of no use.
+struct SplitTokenizer;
+
+impl Tokenizer for SplitTokenizer {
+    fn tokenize<'a>(&self, input: &'a str) -> Vec<&'a str> {
+        input.split_whitespace().collect()
+    }
+
+    fn dictionary(&self) -> Option<String> {
+        Some("split".to_string())
+    }
+}
+
+#[test]
+fn test_unknown_dictionary() {
+    let dir = "/tmp/jets/TEST_JIEBA_UNKNOWN";
+    let _ = std::fs::remove_dir_all(dir);
+    let writer = IndexWriter::open(dir, SplitTokenizer).unwrap();
+    let doc = Document::builder()
+        .put("title", DocValue::from("功夫 熊猫"), FLAG_TOKENIZED)
+        .build();
+    writer.push(doc).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    // the dictionary is not taken for another kind of tokenizer.
+    assert!(IndexReader::open(dir).unwrap().get_tokenizer().is_none());
+    assert!(IndexReader::read_dictionary(dir, JiebaTokenizer::KIND).is_err());
+    assert!(IndexWriter::open(dir, JiebaTokenizer::default()).is_err());
+    assert!(IndexWriter::open(dir, SplitTokenizer).is_ok());
+}
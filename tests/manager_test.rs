@@ -0,0 +1,154 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn doc(i: usize) -> Document {
+    Document::builder()
+        .put("name", DocValue::Text(format!("doc_{}", i)), 0)
+        .put("kind", DocValue::from("film"), 0)
+        .build()
+}
+
+fn films() -> Query {
+    Query::from(Condition::term("kind".to_string(), "film".to_string()))
+}
+
+#[test]
+fn test_send_sync() {
+    assert_send_sync::<IndexReader>();
+    assert_send_sync::<IndexSearcher>();
+    assert_send_sync::<SearcherManager>();
+}
+
+#[test]
+fn test_maybe_refresh() {
+    let path = "/tmp/jets/TEST_MANAGER";
+    let _ = fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    writer.push(doc(0)).unwrap();
+    writer.flush().unwrap();
+
+    let manager = SearcherManager::open(path).unwrap();
+    let before = manager.acquire();
+    assert_eq!(1, before.search(&films()).total_hits());
+    assert!(!manager.maybe_refresh().unwrap());
+
+    writer.push(doc(1)).unwrap();
+    writer.push(doc(2)).unwrap();
+    writer.flush().unwrap();
+    assert!(manager.maybe_refresh().unwrap());
+    assert!(!manager.maybe_refresh().unwrap());
+    let after = manager.acquire();
+    assert_eq!(3, after.search(&films()).total_hits());
+    // searchers acquired before the refresh are still usable.
+    assert_eq!(1, before.search(&films()).total_hits());
+    let found = after.search(&Query::from(Condition::term(
+        "name".to_string(),
+        "doc_2".to_string(),
+    )));
+    assert_eq!(1, found.total_hits());
+}
+
+#[test]
+fn test_refresh_from_writer() {
+    let path = "/tmp/jets/TEST_MANAGER_NRT";
+    let _ = fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    writer.push(doc(0)).unwrap();
+    writer.flush().unwrap();
+    let manager = SearcherManager::open(path).unwrap();
+
+    // the latest commit is older than the near-real-time view.
+    writer.push(doc(1)).unwrap();
+    manager.refresh_from(&writer).unwrap();
+    assert_eq!(2, manager.acquire().search(&films()).total_hits());
+    assert!(!manager.maybe_refresh().unwrap());
+    assert_eq!(2, manager.acquire().search(&films()).total_hits());
+
+    writer.push(doc(2)).unwrap();
+    writer.flush().unwrap();
+    assert!(manager.maybe_refresh().unwrap());
+    assert_eq!(3, manager.acquire().search(&films()).total_hits());
+}
+
+#[test]
+fn test_stress_refresh() {
+    let path = "/tmp/jets/TEST_MANAGER_STRESS";
+    let _ = fs::remove_dir_all(path);
+    let total = 400;
+    let writer = Arc::new(IndexWriter::open(path, JiebaTokenizer::default()).unwrap());
+    let manager = Arc::new(SearcherManager::from(IndexSearcher::from(
+        writer.reader().unwrap(),
+    )));
+    let done = Arc::new(AtomicBool::new(false));
+
+    let indexing: Vec<_> = (0..2)
+        .map(|t| {
+            let writer = writer.clone();
+            thread::spawn(move || {
+                for i in 0..total / 2 {
+                    writer.push(doc(t * total / 2 + i)).unwrap();
+                }
+            })
+        })
+        .collect();
+    let refresher = {
+        let (writer, manager, done) = (writer.clone(), manager.clone(), done.clone());
+        thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                manager.refresh_from(&writer).unwrap();
+                thread::sleep(Duration::from_millis(5));
+            }
+        })
+    };
+    let searching: Vec<_> = (0..4)
+        .map(|_| {
+            let (manager, done) = (manager.clone(), done.clone());
+            thread::spawn(move || {
+                let mut last = 0;
+                let mut rounds = 0;
+                while !done.load(Ordering::SeqCst) || rounds < 10 {
+                    let searcher = manager.acquire();
+                    let hits = searcher.search(&films().limit(5));
+                    // a newer searcher never sees fewer documents.
+                    assert!(hits.total_hits() >= last);
+                    last = hits.total_hits();
+                    for it in hits.documents().unwrap_or_default() {
+                        assert_eq!(Some(&DocValue::from("film")), it.get("kind"));
+                    }
+                    rounds += 1;
+                }
+                // the final refresh happened before done.
+                let hits = manager.acquire().search(&films()).total_hits();
+                assert!(hits >= last);
+                hits
+            })
+        })
+        .collect();
+
+    for it in indexing {
+        it.join().unwrap();
+    }
+    manager.refresh_from(&writer).unwrap();
+    done.store(true, Ordering::SeqCst);
+    refresher.join().unwrap();
+    for it in searching {
+        assert_eq!(total, it.join().unwrap());
+    }
+    let searcher = manager.acquire();
+    assert_eq!(total, searcher.search(&films()).total_hits());
+    for i in [0, total / 2, total - 1] {
+        let name = format!("doc_{}", i);
+        let hits = searcher.search(&Query::from(Condition::term("name".to_string(), name)));
+        assert_eq!(1, hits.total_hits());
+    }
+}
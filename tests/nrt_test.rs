@@ -0,0 +1,88 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use std::fs;
+
+fn doc(name: &str) -> Document {
+    Document::builder()
+        .put("name", DocValue::from(name), 0)
+        .build()
+}
+
+#[test]
+fn test_writer_reader() {
+    let path = "/tmp/jets/TEST_NRT_WRITER";
+    let _ = fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    writer.push(doc("foo")).unwrap();
+    writer.push(doc("bar")).unwrap();
+
+    // buffered documents are visible without commit.
+    let reader = writer.reader().unwrap();
+    assert_eq!(2, reader.num_docs());
+    let ids = reader.find("name", "bar").unwrap();
+    assert_eq!(1, ids.len());
+    assert_eq!(
+        Some(&DocValue::from("bar")),
+        reader.document(ids[0]).unwrap().get("name")
+    );
+    assert_eq!(1, writer.stats().get_flushes());
+
+    writer.push(doc("baz")).unwrap();
+    // a point-in-time reader does not see later documents.
+    assert!(reader.find("name", "baz").map_or(true, |it| it.is_empty()));
+    let next = writer.reader().unwrap();
+    assert_eq!(3, next.num_docs());
+    assert_eq!(1, next.find("name", "baz").unwrap().len());
+    assert_eq!(2, reader.num_docs());
+
+    // nothing flushed since last reader.
+    assert_eq!(3, writer.reader().unwrap().num_docs());
+    assert_eq!(2, writer.stats().get_flushes());
+
+    // uncommitted segments are lost when the writer is dropped.
+    drop(writer);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    assert_eq!(0, writer.counter());
+    drop(writer);
+    assert!(IndexReader::open(path).is_err());
+}
+
+#[test]
+fn test_writer_reader_commit() {
+    let path = "/tmp/jets/TEST_NRT_COMMIT";
+    let _ = fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    writer.push(doc("foo")).unwrap();
+    assert_eq!(1, writer.reader().unwrap().num_docs());
+    // segments flushed by the reader are committed with nothing buffered.
+    writer.flush().unwrap();
+    drop(writer);
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(1, reader.num_docs());
+    assert_eq!(1, reader.find("name", "foo").unwrap().len());
+}
+
+#[test]
+fn test_reopen() {
+    let path = "/tmp/jets/TEST_NRT_REOPEN";
+    let _ = fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    writer.push(doc("foo")).unwrap();
+    writer.flush().unwrap();
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(1, reader.num_docs());
+    assert!(reader.reopen().unwrap().is_none());
+
+    writer.push(doc("bar")).unwrap();
+    writer.push(doc("baz")).unwrap();
+    writer.flush().unwrap();
+    let reopened = reader.reopen().unwrap().unwrap();
+    assert_eq!(3, reopened.num_docs());
+    assert_eq!(1, reopened.find("name", "foo").unwrap().len());
+    assert_eq!(1, reopened.find("name", "baz").unwrap().len());
+    assert_eq!(1, reader.num_docs());
+    assert!(reopened.reopen().unwrap().is_none());
+}
@@ -0,0 +1,26 @@
+extern crate bytes;
+extern crate jets;
+
+use bytes::BytesMut;
+use jets::utils::PackedInts;
+
+#[test]
+fn test_packed_ints() {
+    let mut inputs: Vec<u64> = (0..1000u64)
+        .map(|i| (i * 7919) % 1013 + 1_000_000)
+        .collect();
+    inputs.extend(vec![0, u64::MAX, 42, 42, 42]);
+    inputs.extend(vec![5; 200]);
+    let packed = PackedInts::new(&inputs);
+    let mut bf = BytesMut::new();
+    packed.write_to(&mut bf).unwrap();
+    // 11 bits per value for the first blocks.
+    assert!(bf.len() < inputs.len() * 2 + 128);
+    let decoded = PackedInts::decode(&mut bf.freeze()).unwrap();
+    assert_eq!(inputs.len(), decoded.len());
+    for (i, v) in inputs.iter().enumerate() {
+        assert_eq!(Some(*v), decoded.get(i));
+    }
+    assert_eq!(None, decoded.get(inputs.len()));
+    assert!(PackedInts::new(&[]).is_empty());
+}
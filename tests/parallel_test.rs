@@ -0,0 +1,106 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+fn term(field: &str, word: &str) -> Condition {
+    Condition::term(field.to_string(), word.to_string())
+}
+
+/// Ids and scores of all hits in result order.
+fn ranked(searcher: &IndexSearcher, query: Query) -> Vec<(u64, f32)> {
+    let top = searcher.search(&query.limit(1000));
+    top.documents()
+        .unwrap_or_default()
+        .iter()
+        .map(|it| (it.get_id(), top.score(it.get_id()).unwrap()))
+        .collect()
+}
+
+#[test]
+fn test_parallel_search() {
+    let path = "/tmp/jets/TEST_PARALLEL";
+    let _ = std::fs::remove_dir_all(path);
+    let config = IndexWriterConfig::default().max_buffered_docs(Some(37));
+    let writer = IndexWriter::open_with_config(path, JiebaTokenizer::default(), config).unwrap();
+    let titles = ["北京欢迎你", "上海滩", "北京人在纽约", "我爱北京天安门"];
+    for i in 0..300 {
+        let mut doc = Document::builder()
+            .put("name", DocValue::Text(format!("doc_{}", i)), 0)
+            .put("kind", DocValue::from(["film", "tv"][i % 2]), 0)
+            .put("year", DocValue::Long(1990 + (i % 7) as i64), 0)
+            .put("title", DocValue::from(titles[i % 4]), FLAG_TOKENIZED);
+        if i % 5 != 0 {
+            doc = doc.put("star", DocValue::Double((i % 10) as f64), 0);
+        }
+        writer.push(doc.build()).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let sequential = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let mut parallel = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let executor = Executor::new(4).unwrap();
+    assert_eq!(4, executor.threads());
+    parallel.set_executor(executor);
+
+    let queries: Vec<fn() -> Query> = vec![
+        || Query::from(term("kind", "film")),
+        || Query::from(term("title", "北京")),
+        || {
+            Query::from(Condition::group(
+                Operator::OR,
+                vec![term("title", "北京"), term("title", "上海").boost(2.0)],
+            ))
+        },
+        || {
+            Query::from(Condition::group(
+                Operator::AND,
+                vec![term("kind", "tv"), term("title", "北京")],
+            ))
+        },
+        || Query::from(term("title", "北京")).sort(SortField::desc("star")),
+        || {
+            Query::from(term("kind", "film"))
+                .sort(SortField::asc("year"))
+                .sort(SortField::desc("star").missing(Missing::First))
+        },
+        || {
+            Query::from(Condition::from(
+                FunctionScore::new(term("title", "北京"))
+                    .function(ScoreFunction::field_value_factor(
+                        FieldValueFactor::new("star").missing(1.0),
+                    ))
+                    .boost_mode(BoostMode::Multiply),
+            ))
+        },
+        || Query::from(term("title", "unknown")),
+    ];
+    for query in queries {
+        assert_eq!(ranked(&sequential, query()), ranked(&parallel, query()));
+        let (a, b) = (
+            sequential.search(&query().offset(3).limit(5)),
+            parallel.search(&query().offset(3).limit(5)),
+        );
+        assert_eq!(a.total_hits(), b.total_hits());
+        let ids = |docs: Option<Vec<Document>>| -> Vec<u64> {
+            docs.unwrap_or_default()
+                .iter()
+                .map(|it| it.get_id())
+                .collect()
+        };
+        assert_eq!(ids(a.documents()), ids(b.documents()));
+    }
+
+    // collapsed groups are built from the merged hits.
+    let query = Query::from(term("title", "北京")).collapse(Collapse::new("year"));
+    let (a, b) = (sequential.search(&query), parallel.search(&query));
+    assert_eq!(a.total_groups(), b.total_groups());
+    let groups = |top: &TopDocs| -> Vec<(u64, usize)> {
+        top.groups()
+            .iter()
+            .map(|it| (it.get_id(), it.get_total()))
+            .collect()
+    };
+    assert_eq!(groups(&a), groups(&b));
+}
@@ -0,0 +1,95 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+#[test]
+fn test_pinyin_filter() {
+    let tokens = vec![Token::new("恶魔", 0), Token::new("emo", 1)];
+    let found: Vec<String> = PinyinFilter::default()
+        .filter(tokens)
+        .iter()
+        .map(|it| it.get_text().to_string())
+        .collect();
+    assert_eq!(
+        vec![
+            "恶魔", "èmó", "ěmó", "wùmó", "wūmó", "emo", "wumo", "em", "wm", "e", "wu", "mo", "emo"
+        ],
+        found
+    );
+
+    let found = PinyinFilter::default()
+        .keep_original(false)
+        .full(false)
+        .syllables(false)
+        .filter(vec![Token::new("北京", 3)]);
+    assert_eq!(
+        vec![
+            Token::new("beijing", 3).kind(TOKEN_TYPE_PINYIN),
+            Token::new("bj", 3).kind(TOKEN_TYPE_PINYIN)
+        ],
+        found
+    );
+}
+
+#[test]
+fn test_pinyin_polyphones() {
+    let readings = PinyinFilter::readings("行长").unwrap();
+    assert_eq!(vec!["xíng", "háng", "héng", "xìng", "hàng"], readings[0]);
+    assert_eq!(vec!["zhǎng", "cháng"], readings[1]);
+
+    let found: Vec<String> = PinyinFilter::default()
+        .keep_original(false)
+        .full(false)
+        .initials(false)
+        .syllables(false)
+        .max_combinations(4)
+        .filter(vec![Token::new("行长", 0)])
+        .iter()
+        .map(|it| it.get_text().to_string())
+        .collect();
+    assert_eq!(
+        vec!["xingzhang", "xingchang", "hangzhang", "hangchang"],
+        found
+    );
+}
+
+#[test]
+fn test_pinyin_search() {
+    let path = "/tmp/jets/TEST_PINYIN";
+    let _ = std::fs::remove_dir_all(path);
+    let mut writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    writer.set_analyzer(
+        "title",
+        Analyzer::builder().filter(PinyinFilter::default()).build(),
+    );
+    let doc = Document::builder()
+        .put("title", DocValue::from("恶魔"), FLAG_TOKENIZED)
+        .build();
+    writer.push(doc).unwrap();
+    writer.flush().unwrap();
+
+    let mut searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let search = |searcher: &IndexSearcher, word: &str| {
+        let q = Query::from(Condition::term("title".to_string(), word.to_string()));
+        searcher.search(&q).total_hits()
+    };
+    for word in ["恶魔", "emo", "em", "wumo"] {
+        assert_eq!(1, search(&searcher, word));
+    }
+
+    // query values are split by whitespace without a tokenizer, then match the syllables.
+    searcher.set_analyzer("title", Analyzer::default());
+    for word in ["emo", "e mo", "wu mo"] {
+        assert_eq!(1, search(&searcher, word));
+    }
+    assert_eq!(0, search(&searcher, "e ma"));
+
+    // or tokenized like the writer.
+    searcher.set_tokenizer(JiebaTokenizer::default());
+    for word in ["恶魔", "emo", "e mo", "wu mo"] {
+        assert_eq!(1, search(&searcher, word));
+    }
+    assert_eq!(0, search(&searcher, "e ma"));
+}
@@ -0,0 +1,79 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+fn term(word: &str) -> Condition {
+    Condition::term("tag".to_string(), word.to_string())
+}
+
+#[test]
+fn test_profile() {
+    let path = "/tmp/jets/TEST_PROFILE";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let docs = vec![
+        vec!["a", "b"],
+        vec!["a"],
+        vec!["b", "c"],
+        vec!["c"],
+        vec!["a"],
+    ];
+    for (i, tags) in docs.into_iter().enumerate() {
+        let mut doc = Document::builder();
+        for it in tags {
+            doc = doc.put("tag", DocValue::from(it), 0);
+        }
+        writer.push(doc.build()).unwrap();
+        if i == 2 {
+            writer.flush().unwrap();
+        }
+    }
+    writer.flush().unwrap();
+
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let q = Query::from(Condition::group(
+        Operator::AND,
+        vec![
+            term("a"),
+            Condition::group(Operator::OR, vec![term("b"), term("x")]),
+        ],
+    ));
+    let top = searcher.search(&q);
+    assert!(top.profile().is_none());
+
+    let top = searcher.search(&q.profile(true));
+    let profile = top.profile().unwrap();
+    assert!(profile.get_fetch().is_none());
+    let root = profile.get_query();
+    assert_eq!("AND", root.get_description());
+    assert_eq!(1, root.get_hits());
+    assert_eq!(0, root.get_fst_lookups());
+    assert_eq!(2, root.get_children().len());
+
+    let a = &root.get_children()[0];
+    assert_eq!("term tag:a", a.get_description());
+    assert_eq!(3, a.get_hits());
+    assert_eq!(2, a.get_fst_lookups());
+    let mut sizes: Vec<(u32, usize)> = a
+        .get_postings()
+        .iter()
+        .map(|it| (it.get_segment(), it.get_size()))
+        .collect();
+    sizes.sort();
+    assert_eq!(vec![(0, 2), (1, 1)], sizes);
+    assert!(root.get_elapsed() >= a.get_elapsed());
+
+    let or = &root.get_children()[1];
+    assert_eq!(2, or.get_hits());
+    let x = &or.get_children()[1];
+    assert_eq!(2, x.get_fst_lookups());
+    assert!(x.get_postings().is_empty());
+    assert_eq!(0, x.get_hits());
+
+    assert_eq!(1, top.documents().unwrap().len());
+    let profile = top.profile().unwrap();
+    assert!(profile.get_fetch().is_some());
+    assert!(profile.to_string().contains("term tag:b"));
+}
@@ -0,0 +1,41 @@
+extern crate jets;
+
+use jets::analysis::*;
+
+#[test]
+fn test_script_runs() {
+    let runs = Script::runs("我爱iPhone 11，真香! Привет");
+    assert_eq!(
+        vec![
+            (Script::Han, "我爱"),
+            (Script::Latin, "iPhone 11，"),
+            (Script::Han, "真香! "),
+            (Script::Cyrillic, "Привет"),
+        ],
+        runs
+    );
+}
+
+#[test]
+fn test_script_tokenizer() {
+    let tokenizer = ScriptTokenizer::builder()
+        .route(Script::Han, JiebaTokenizer::default())
+        .route(Script::Latin, StandardTokenizer)
+        .build();
+    let input = "北京欢迎you and me!北京有长城";
+    let tokens = Analyzer::builder()
+        .stopwords(
+            StopWordsUnion::default()
+                .with(StopWordsCN)
+                .with(StopWordsEN),
+        )
+        .build()
+        .analyze(&tokenizer, input);
+    let words: Vec<&str> = tokens.iter().map(|it| it.get_text()).collect();
+    assert_eq!(vec!["北京", "欢迎", "you", "me", "北京", "长城"], words);
+    let positions: Vec<u32> = tokens.iter().map(|it| it.get_position()).collect();
+    assert_eq!(vec![0, 1, 2, 4, 5, 7], positions);
+    for it in tokens.iter() {
+        assert_eq!(it.get_text(), &input[it.get_start()..it.get_end()]);
+    }
+}
@@ -0,0 +1,112 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use std::fs;
+use std::path::Path;
+
+fn doc(name: String) -> Document {
+    Document::builder()
+        .put("name", DocValue::Text(name), 0)
+        .build()
+}
+
+fn push(writer: &IndexWriter<JiebaTokenizer>, from: usize, to: usize) {
+    for i in from..to {
+        writer.push(doc(format!("doc_{}", i))).unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+fn checkpoints(path: &str) -> usize {
+    fs::read_dir(path)
+        .unwrap()
+        .filter(|it| {
+            let name = it.as_ref().unwrap().file_name();
+            name.to_str().unwrap().starts_with("snapshot_")
+        })
+        .count()
+}
+
+#[test]
+fn test_snapshot_backup() {
+    let path = "/tmp/jets/TEST_SNAPSHOT";
+    let backup = "/tmp/jets/TEST_SNAPSHOT_BACKUP";
+    let _ = fs::remove_dir_all(path);
+    let _ = fs::remove_dir_all(backup);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    assert!(writer.snapshot().is_err());
+    push(&writer, 0, 10);
+
+    let first = writer.snapshot().unwrap();
+    assert_eq!(1, first.generation());
+    assert_eq!(1, checkpoints(path));
+    // the pinned commit survives later commits.
+    push(&writer, 10, 20);
+    for name in first.files() {
+        assert!(Path::new(path).join(name).exists(), "{}", name);
+    }
+    let stats = first.backup_to(backup).unwrap();
+    assert!(stats.get_copied_files() >= 3);
+    assert!(stats.get_copied_bytes() > 0);
+    assert_eq!(0, stats.get_reused_files());
+    drop(first);
+    assert_eq!(0, checkpoints(path));
+
+    // the backup opens as a normal index, at the time of snapshot.
+    let reader = IndexReader::open(backup).unwrap();
+    assert_eq!(10, reader.num_docs());
+    let ids = reader.find("name", "doc_9").unwrap();
+    assert_eq!(
+        Some(&DocValue::from("doc_9")),
+        reader.document(ids[0]).unwrap().get("name")
+    );
+    assert_eq!(0, reader.find("name", "doc_10").unwrap().len());
+    drop(reader);
+
+    // segments in backup already are skipped.
+    let second = writer.snapshot().unwrap();
+    assert_eq!(2, second.generation());
+    let stats = second.backup_to(backup).unwrap();
+    assert!(stats.get_reused_files() >= 2);
+    // a file of the same length with another checksum is copied again.
+    let name = second
+        .files()
+        .iter()
+        .find(|it| it.ends_with(".index"))
+        .unwrap();
+    let file = Path::new(backup).join(name);
+    let mut bs = fs::read(&file).unwrap();
+    let n = bs.len();
+    bs[n - 1] ^= 0xff;
+    fs::write(&file, bs).unwrap();
+    second.backup_to(backup).unwrap();
+    let source = Path::new(path).join(name);
+    assert_eq!(fs::read(source).unwrap(), fs::read(&file).unwrap());
+    IndexReader::open(backup)
+        .unwrap()
+        .check_integrity()
+        .unwrap();
+    // rewritten files are renamed in place, none is left pending.
+    for dir in [backup.to_string(), format!("{}/data", backup)] {
+        for it in fs::read_dir(dir).unwrap() {
+            let name = it.unwrap().file_name();
+            assert!(!name.to_str().unwrap().starts_with("pending_"));
+        }
+    }
+    drop(second);
+    assert!(!Path::new(backup).join("segments_1").exists());
+    push(&writer, 20, 30);
+    assert!(!Path::new(path).join("segments_1").exists());
+    assert!(!Path::new(path).join("segments_2").exists());
+    drop(writer);
+
+    // restore by opening the backup.
+    let writer = IndexWriter::open(backup, JiebaTokenizer::default()).unwrap();
+    push(&writer, 100, 105);
+    let reader = writer.reader().unwrap();
+    assert_eq!(25, reader.num_docs());
+    assert_eq!(1, reader.find("name", "doc_19").unwrap().len());
+    assert_eq!(1, reader.find("name", "doc_104").unwrap().len());
+    assert_eq!(0, reader.find("name", "doc_20").unwrap().len());
+}
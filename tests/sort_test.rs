@@ -0,0 +1,72 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+#[test]
+fn test_sort_by_fields() {
+    let path = "/tmp/jets/TEST_SORT";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let films = vec![
+        ("a", "1994", Some(9.2)),
+        ("b", "2013", Some(7.5)),
+        ("c", "1994", Some(8.6)),
+        ("d", "2001", None),
+        ("e", "2013", Some(8.6)),
+    ];
+    for (i, (name, year, star)) in films.into_iter().enumerate() {
+        let mut doc = Document::builder()
+            .put("name", DocValue::from(name), 0)
+            .put("kind", DocValue::from("film"), 0)
+            .put("year", DocValue::from(year), 0);
+        if let Some(n) = star {
+            doc = doc.put("star", DocValue::Double(n), 0);
+        }
+        writer.push(doc.build()).unwrap();
+        if i == 1 {
+            writer.flush().unwrap();
+        }
+    }
+    writer.flush().unwrap();
+
+    let searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let names = |q: Query| -> Vec<String> {
+        searcher
+            .search(&q)
+            .documents()
+            .unwrap()
+            .iter()
+            .map(|it| it.get("name").unwrap().to_string())
+            .collect()
+    };
+    let films = || Query::from(Condition::term("kind".to_string(), "film".to_string()));
+
+    assert_eq!(
+        vec!["a", "c", "e", "b", "d"],
+        names(films().sort(SortField::desc("star")))
+    );
+    assert_eq!(
+        vec!["b", "c", "e", "a", "d"],
+        names(films().sort(SortField::asc("star")))
+    );
+    assert_eq!(
+        vec!["d", "b", "c", "e", "a"],
+        names(films().sort(SortField::asc("star").missing(Missing::First)))
+    );
+    // newest first, then highest rated.
+    assert_eq!(
+        vec!["e", "b", "d", "a", "c"],
+        names(
+            films()
+                .sort(SortField::desc("year"))
+                .sort(SortField::desc("star"))
+        )
+    );
+    // unknown fields keep doc id order.
+    assert_eq!(
+        vec!["a", "b", "c", "d", "e"],
+        names(films().sort(SortField::desc("unknown")))
+    );
+}
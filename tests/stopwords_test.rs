@@ -0,0 +1,68 @@
+extern crate jets;
+
+use jets::analysis::*;
+use std::fs::File;
+use std::io::prelude::*;
+
+struct Whitespace;
+
+impl Tokenizer for Whitespace {
+    fn tokenize<'a>(&self, input: &'a str) -> Vec<&'a str> {
+        input.split_whitespace().collect()
+    }
+}
+
+fn words(analyzer: &Analyzer, input: &str) -> Vec<String> {
+    analyzer
+        .analyze(&Whitespace, input)
+        .iter()
+        .map(|it| it.get_text().to_string())
+        .collect()
+}
+
+#[test]
+fn test_stopwords_file() {
+    std::fs::create_dir_all("/tmp/jets").unwrap();
+    let path = "/tmp/jets/TEST_STOPWORDS.txt";
+    let mut f = File::create(path).unwrap();
+    f.write_all("# films\n电影\n\n影片\n".as_bytes()).unwrap();
+    let stopwords = StopWordsSet::open(path).unwrap();
+    assert_eq!(2, stopwords.len());
+    assert!(stopwords.contains("影片"));
+    assert!(!stopwords.contains("# films"));
+}
+
+#[test]
+fn test_stopwords_compose() {
+    let union = StopWordsUnion::default()
+        .with(StopWordsEN)
+        .with(StopWordsSet::from(vec!["电影"]));
+    assert!(union.contains("The"));
+    assert!(union.contains("电影"));
+    assert!(!union.contains("恶魔"));
+
+    let custom = StopWordsOverride::new(StopWordsCN).keep("的").stop("电影");
+    assert!(!custom.contains("的"));
+    assert!(custom.contains("电影"));
+    assert!(custom.contains("了"));
+}
+
+#[test]
+fn test_analyzer_stopwords() {
+    let input = "the 恶魔 的 film";
+    assert_eq!(
+        vec!["the", "恶魔", "film"],
+        words(&Analyzer::default(), input)
+    );
+    let analyzer = Analyzer::builder().stopwords(StopWordsEN).build();
+    assert_eq!(vec!["恶魔", "的", "film"], words(&analyzer, input));
+    let analyzer = Analyzer::builder().no_stopwords().build();
+    assert_eq!(vec!["the", "恶魔", "的", "film"], words(&analyzer, input));
+
+    let positions: Vec<u32> = Analyzer::default()
+        .analyze(&Whitespace, input)
+        .iter()
+        .map(|it| it.get_position())
+        .collect();
+    assert_eq!(vec![0, 1, 3], positions);
+}
@@ -33,3 +33,23 @@ fn test_document_store() {
         }
     }
 }
+
+#[test]
+fn test_shared_store() {
+    init();
+    let path = "/tmp/jets/test_store_shared";
+    let _ = std::fs::remove_dir_all(path);
+    let first = DocValueStore::open(path).unwrap();
+    // another handle of the same path is not locked out.
+    let second = DocValueStore::open(&format!("{}/", path)).unwrap();
+    first.write(1, 0, &DocValue::from("foo")).unwrap();
+    assert_eq!(
+        Some(DocValue::from("foo")),
+        second.get(1, 0, DocValue::from("foo").get_type()).unwrap()
+    );
+    drop(first);
+    drop(second);
+    // reopened after all handles are dropped.
+    let third = DocValueStore::open(path).unwrap();
+    assert!(third.get(1, 0, DocValue::from("foo").get_type()).unwrap().is_some());
+}
@@ -0,0 +1,120 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use jets::search::*;
+
+static RULES: &str = r#"
+# equivalent synonyms
+北京, 北平, 京城
+# explicit mappings
+北大 => 北京 大学
+帝都, 首都 => 北京
+"#;
+
+fn texts(tokens: &[Token]) -> Vec<(&str, u32, u32)> {
+    tokens
+        .iter()
+        .map(|it| (it.get_text(), it.get_position(), it.get_span()))
+        .collect()
+}
+
+#[test]
+fn test_synonym_filter() {
+    let filter = SynonymFilter::parse(RULES, true).unwrap();
+    let tokens = filter.filter(vec![
+        Token::new("我", 0),
+        Token::new("爱", 1),
+        Token::new("北平", 2),
+    ]);
+    assert_eq!(
+        vec![
+            ("我", 0, 1),
+            ("爱", 1, 1),
+            ("北京", 2, 1),
+            ("北平", 2, 1),
+            ("京城", 2, 1)
+        ],
+        texts(&tokens)
+    );
+
+    let tokens = filter.filter(vec![Token::new("北大", 0), Token::new("食堂", 1)]);
+    assert_eq!(
+        vec![("北京", 0, 1), ("大学", 1, 1), ("食堂", 2, 1)],
+        texts(&tokens)
+    );
+
+    let filter = SynonymFilter::parse("北京 大学, 北大", true).unwrap();
+    let tokens = filter.filter(vec![Token::new("北京", 0), Token::new("大学", 1)]);
+    assert_eq!(
+        vec![("北京", 0, 1), ("北大", 0, 2), ("大学", 1, 1)],
+        texts(&tokens)
+    );
+
+    let filter = SynonymFilter::parse("北京, 北平, 京城", false).unwrap();
+    let tokens = filter.filter(vec![Token::new("京城", 0)]);
+    assert_eq!(vec![("北京", 0, 1)], texts(&tokens));
+}
+
+#[test]
+fn test_synonym_search() {
+    let path = "/tmp/jets/TEST_SYNONYM";
+    let _ = std::fs::remove_dir_all(path);
+    let mut writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    let index_time = SynonymFilter::parse("北京, 北平, 京城", true).unwrap();
+    writer.set_analyzer("content", Analyzer::builder().filter(index_time).build());
+    for it in ["北平有长城", "京城的大学", "上海是我们的家"] {
+        let doc = Document::builder()
+            .put("content", DocValue::from(it), FLAG_TOKENIZED)
+            .build();
+        writer.push(doc).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let mut searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let search = |searcher: &IndexSearcher, word: &str| {
+        let q = Query::from(Condition::term("content".to_string(), word.to_string()));
+        match searcher.search(&q).documents() {
+            Some(docs) => docs.len(),
+            None => 0,
+        }
+    };
+    assert_eq!(2, search(&searcher, "京城"));
+    assert_eq!(0, search(&searcher, "帝都"));
+
+    let query_time = SynonymFilter::parse(RULES, true).unwrap();
+    searcher.set_analyzer("content", Analyzer::builder().filter(query_time).build());
+    assert_eq!(2, search(&searcher, "帝都"));
+    assert_eq!(1, search(&searcher, "北大"));
+}
+
+#[test]
+fn test_synonym_query_tokens() {
+    let path = "/tmp/jets/TEST_SYNONYM_TOKENS";
+    let _ = std::fs::remove_dir_all(path);
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    for it in ["北大的食堂", "北京的大学", "上海的大学"] {
+        let doc = Document::builder()
+            .put("content", DocValue::from(it), FLAG_TOKENIZED)
+            .build();
+        writer.push(doc).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let mut searcher = IndexSearcher::from(IndexReader::open(path).unwrap());
+    let query_time = SynonymFilter::parse("北京 大学, 北大", true).unwrap();
+    searcher.set_analyzer("content", Analyzer::builder().filter(query_time).build());
+    let search = |searcher: &IndexSearcher, word: &str| {
+        let q = Query::from(Condition::term("content".to_string(), word.to_string()));
+        searcher.search(&q).total_hits()
+    };
+    // the value is split by whitespace without a tokenizer.
+    assert_eq!(2, search(&searcher, "北京 大学"));
+    assert_eq!(2, search(&searcher, "北大"));
+    assert_eq!(0, search(&searcher, "上海的大学"));
+
+    searcher.set_tokenizer(JiebaTokenizer::default());
+    assert_eq!(2, search(&searcher, "北京 大学"));
+    assert_eq!(2, search(&searcher, "北大"));
+    assert_eq!(1, search(&searcher, "上海的大学"));
+}
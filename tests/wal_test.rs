@@ -0,0 +1,173 @@
+extern crate jets;
+
+use jets::analysis::*;
+use jets::core::*;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+fn open(path: &str, policy: SyncPolicy) -> IndexWriter<JiebaTokenizer> {
+    let config = IndexWriterConfig::default().wal(policy);
+    IndexWriter::open_with_config(path, JiebaTokenizer::default(), config).unwrap()
+}
+
+fn doc(name: &str, star: f64) -> Document {
+    Document::builder()
+        .put("name", DocValue::from(name), 0)
+        .put("title", DocValue::from("北京欢迎你"), FLAG_TOKENIZED)
+        .put("star", DocValue::Double(star), 0)
+        .put("year", DocValue::Long(-1), FLAG_NOT_STORED)
+        .build()
+}
+
+#[test]
+fn test_wal_replay() {
+    let path = "/tmp/jets/TEST_WAL";
+    let _ = fs::remove_dir_all(path);
+    let wal = Path::new(path).join("wal.log");
+
+    let writer = open(path, SyncPolicy::Always);
+    writer.push(doc("foo", 7.5)).unwrap();
+    writer.push(doc("bar", 8.0)).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(0, fs::metadata(&wal).unwrap().len());
+    writer.push(doc("baz", 9.0)).unwrap();
+    writer.push(doc("qux", 9.5)).unwrap();
+    // crash before flush, with a torn record at tail.
+    drop(writer);
+    let size = fs::metadata(&wal).unwrap().len();
+    OpenOptions::new()
+        .append(true)
+        .open(&wal)
+        .unwrap()
+        .write_all(&[0, 0, 0, 42, 1, 2])
+        .unwrap();
+
+    let writer = open(path, SyncPolicy::Interval(Duration::from_millis(100)));
+    assert_eq!(2, writer.counter());
+    assert_eq!(size, fs::metadata(&wal).unwrap().len());
+    writer.flush().unwrap();
+    assert_eq!(0, fs::metadata(&wal).unwrap().len());
+    drop(writer);
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(4, reader.num_docs());
+    let ids = reader.find("name", "qux").unwrap();
+    assert_eq!(1, ids.len());
+    let found = reader.document(ids[0]).unwrap();
+    assert_eq!(Some(&DocValue::Double(9.5)), found.get("star"));
+    assert_eq!(None, found.get("year"));
+    assert_eq!(4, reader.find("year", DocValue::Long(-1)).unwrap().len());
+    assert_eq!(4, reader.find("title", "北京").unwrap().len());
+}
+
+#[test]
+fn test_wal_committed() {
+    let path = "/tmp/jets/TEST_WAL_COMMITTED";
+    let _ = fs::remove_dir_all(path);
+    let wal = Path::new(path).join("wal.log");
+
+    let writer = open(path, SyncPolicy::Always);
+    writer.push(doc("foo", 1.0)).unwrap();
+    writer.push(doc("bar", 2.0)).unwrap();
+    let logged = fs::read(&wal).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    // crash after commit, before the log is truncated.
+    fs::write(&wal, &logged).unwrap();
+
+    let writer = open(path, SyncPolicy::Always);
+    assert_eq!(0, writer.counter());
+    writer.push(doc("baz", 3.0)).unwrap();
+    drop(writer);
+    let writer = open(path, SyncPolicy::Always);
+    assert_eq!(1, writer.counter());
+    writer.flush().unwrap();
+    drop(writer);
+
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(3, reader.num_docs());
+    assert_eq!(1, reader.find("name", "foo").unwrap().len());
+    assert_eq!(1, reader.find("name", "baz").unwrap().len());
+}
+
+#[test]
+fn test_wal_disabled() {
+    let path = "/tmp/jets/TEST_WAL_DISABLED";
+    let _ = fs::remove_dir_all(path);
+    let wal = Path::new(path).join("wal.log");
+
+    let writer = open(path, SyncPolicy::Never);
+    writer.push(doc("foo", 1.0)).unwrap();
+    drop(writer);
+
+    // the log is replayed even if disabled, then removed after commit.
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    writer.push(doc("bar", 2.0)).unwrap();
+    assert_eq!(2, writer.counter());
+    writer.flush().unwrap();
+    assert!(!wal.exists());
+    drop(writer);
+
+    let writer = IndexWriter::open(path, JiebaTokenizer::default()).unwrap();
+    assert_eq!(0, writer.counter());
+    writer.push(doc("baz", 3.0)).unwrap();
+    drop(writer);
+    assert_eq!(2, IndexReader::open(path).unwrap().num_docs());
+}
+
+#[test]
+fn test_wal_replay_flush() {
+    let path = "/tmp/jets/TEST_WAL_REPLAY_FLUSH";
+    let _ = fs::remove_dir_all(path);
+
+    let writer = open(path, SyncPolicy::Always);
+    for i in 0..25 {
+        writer.push(doc(&format!("doc{}", i), i as f64)).unwrap();
+    }
+    drop(writer);
+
+    // replay is flushed by the limits, but not committed before the whole log is.
+    let config = IndexWriterConfig::default()
+        .wal(SyncPolicy::Always)
+        .max_buffered_docs(Some(10));
+    let writer = IndexWriter::open_with_config(path, JiebaTokenizer::default(), config).unwrap();
+    assert_eq!(2, writer.stats().get_auto_flushes());
+    assert_eq!(5, writer.stats().get_buffered_docs());
+    assert_eq!(25, writer.counter());
+    drop(writer);
+
+    let writer = open(path, SyncPolicy::Always);
+    assert_eq!(25, writer.counter());
+    writer.flush().unwrap();
+    drop(writer);
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(25, reader.num_docs());
+    assert_eq!(1, reader.find("name", "doc24").unwrap().len());
+}
+
+#[test]
+fn test_wal_rejected() {
+    let path = "/tmp/jets/TEST_WAL_REJECTED";
+    let _ = fs::remove_dir_all(path);
+
+    let writer = open(path, SyncPolicy::Always);
+    writer.push(doc("foo", 1.0)).unwrap();
+    // "star" is double, rejected before logged.
+    let bad = Document::builder()
+        .put("name", DocValue::from("bar"), 0)
+        .put("star", DocValue::from("bad"), 0)
+        .build();
+    assert!(writer.push(bad).is_err());
+    writer.push(doc("baz", 3.0)).unwrap();
+    drop(writer);
+
+    let writer = open(path, SyncPolicy::Always);
+    assert_eq!(2, writer.counter());
+    writer.flush().unwrap();
+    drop(writer);
+    let reader = IndexReader::open(path).unwrap();
+    assert_eq!(2, reader.num_docs());
+    assert_eq!(0, reader.find("name", "bar").unwrap().len());
+}